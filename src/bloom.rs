@@ -0,0 +1,154 @@
+use crate::color::Color;
+
+// Desviación y radio (en píxeles de cada nivel, sin supermuestreo) del desenfoque gaussiano
+const BLUR_SIGMA: f32 = 1.5;
+const BLUR_RADIUS: i64 = 4;
+
+// Resplandor alrededor de lo que supera `threshold` de luminancia: se extraen las
+// partes brillantes, se desenfocan a varias resoluciones (cada nivel a la mitad
+// del anterior, así el halo llega lejos sin núcleos enormes) y se suman a la imagen
+#[derive(Clone, Copy, Debug)]
+pub struct Bloom {
+    // Luminancia lineal a partir de la que algo brilla
+    pub threshold: f32,
+    // Cuánto del resplandor se suma a la imagen
+    pub intensity: f32,
+    // Niveles de reducción; más niveles = halo más amplio
+    pub levels: usize,
+    // Píxeles de la imagen por píxel de salida (el factor de SSAA); agranda el
+    // desenfoque para que el halo mida lo mismo con y sin supermuestreo
+    pub scale: f32,
+}
+
+impl Default for Bloom {
+    fn default() -> Self {
+        Bloom { threshold: 1.0, intensity: 1.0, levels: 5, scale: 1.0 }
+    }
+}
+
+impl Bloom {
+    // El mismo bloom para una imagen `factor` veces más grande
+    pub fn scaled(self, factor: usize) -> Bloom {
+        Bloom { scale: self.scale * factor as f32, ..self }
+    }
+
+    // Suma el resplandor a `pixels`, una imagen HDR de width x height
+    pub fn apply(&self, pixels: &mut [Color], width: usize, height: usize) {
+        let bright = Image {
+            width,
+            height,
+            pixels: pixels.iter().map(|color| self.bright_pass(*color)).collect(),
+        };
+
+        let kernel = gaussian_kernel(self.scale);
+        let mut level = bright;
+        let mut blurred = Vec::with_capacity(self.levels);
+        for _ in 0..self.levels {
+            if level.width < 2 || level.height < 2 {
+                break;
+            }
+            level = level.downsample();
+            blurred.push(level.blur(&kernel));
+        }
+        if blurred.is_empty() {
+            return;
+        }
+
+        // Todos los niveles pesan lo mismo
+        let weight = self.intensity / blurred.len() as f32;
+        for y in 0..height {
+            for x in 0..width {
+                let glow = blurred.iter().fold(Color::black(), |sum, level| {
+                    let scale = level.width as f32 / width as f32;
+                    sum + level.sample((x as f32 + 0.5) * scale - 0.5, (y as f32 + 0.5) * scale - 0.5)
+                });
+                let pixel = &mut pixels[y * width + x];
+                *pixel = *pixel + glow * weight;
+            }
+        }
+    }
+
+    // Conserva solo lo que pasa del umbral, con una transición suave para que el
+    // halo no aparezca de golpe
+    fn bright_pass(&self, color: Color) -> Color {
+        let luminance = color.luminance();
+        if luminance <= self.threshold {
+            return Color::black();
+        }
+
+        color * ((luminance - self.threshold) / luminance)
+    }
+}
+
+struct Image {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    fn pixel(&self, x: i64, y: i64) -> Color {
+        let x = x.clamp(0, self.width as i64 - 1) as usize;
+        let y = y.clamp(0, self.height as i64 - 1) as usize;
+        self.pixels[y * self.width + x]
+    }
+
+    // Mitad de resolución promediando bloques de 2x2
+    fn downsample(&self) -> Image {
+        let (width, height) = ((self.width / 2).max(1), (self.height / 2).max(1));
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height as i64 {
+            for x in 0..width as i64 {
+                pixels.push(Color::average(&[
+                    self.pixel(2 * x, 2 * y),
+                    self.pixel(2 * x + 1, 2 * y),
+                    self.pixel(2 * x, 2 * y + 1),
+                    self.pixel(2 * x + 1, 2 * y + 1),
+                ]));
+            }
+        }
+        Image { width, height, pixels }
+    }
+
+    // Gaussiano separable: una pasada horizontal y otra vertical
+    fn blur(&self, kernel: &[f32]) -> Image {
+        let pass = |image: &Image, dx: i64, dy: i64| {
+            let mut pixels = Vec::with_capacity(image.pixels.len());
+            for y in 0..image.height as i64 {
+                for x in 0..image.width as i64 {
+                    let radius = (kernel.len() / 2) as i64;
+                    let color = kernel.iter().enumerate().fold(Color::black(), |sum, (i, &weight)| {
+                        let offset = i as i64 - radius;
+                        sum + image.pixel(x + dx * offset, y + dy * offset) * weight
+                    });
+                    pixels.push(color);
+                }
+            }
+            Image { width: image.width, height: image.height, pixels }
+        };
+
+        pass(&pass(self, 1, 0), 0, 1)
+    }
+
+    // Bilineal en coordenadas de píxel (los centros están en enteros)
+    fn sample(&self, x: f32, y: f32) -> Color {
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = self.pixel(x0, y0).lerp(&self.pixel(x0 + 1, y0), tx);
+        let bottom = self.pixel(x0, y0 + 1).lerp(&self.pixel(x0 + 1, y0 + 1), tx);
+        top.lerp(&bottom, ty)
+    }
+}
+
+// Pesos normalizados del gaussiano, con desviación y radio multiplicados por `scale`
+fn gaussian_kernel(scale: f32) -> Vec<f32> {
+    let sigma = BLUR_SIGMA * scale;
+    let radius = (BLUR_RADIUS as f32 * scale).ceil() as i64;
+    let weights: Vec<f32> = (-radius..=radius)
+        .map(|i| (-((i * i) as f32) / (2.0 * sigma * sigma)).exp())
+        .collect();
+    let total: f32 = weights.iter().sum();
+    weights.into_iter().map(|weight| weight / total).collect()
+}
@@ -0,0 +1,100 @@
+use nalgebra_glm::{Mat4, Vec3, Vec4};
+use crate::vertex::Vertex;
+
+// Margen en x/y: solo se recortan los triángulos que salen mucho de la pantalla,
+// el resto lo descarta el rectángulo envolvente del rasterizador
+const GUARD_BAND: f32 = 2.0;
+
+#[derive(Clone, Copy)]
+enum Plane {
+    Near,
+    Far,
+    Left,
+    Right,
+    Bottom,
+    Top,
+}
+
+const PLANES: [Plane; 6] = [Plane::Near, Plane::Far, Plane::Left, Plane::Right, Plane::Bottom, Plane::Top];
+
+impl Plane {
+    // Distancia con signo al plano; >= 0 significa dentro
+    fn distance(self, p: &Vec4) -> f32 {
+        match self {
+            Plane::Near => p.z + p.w,
+            Plane::Far => p.w - p.z,
+            Plane::Left => p.x + GUARD_BAND * p.w,
+            Plane::Right => GUARD_BAND * p.w - p.x,
+            Plane::Bottom => p.y + GUARD_BAND * p.w,
+            Plane::Top => GUARD_BAND * p.w - p.y,
+        }
+    }
+}
+
+fn is_inside(vertex: &Vertex) -> bool {
+    PLANES.iter().all(|plane| plane.distance(&vertex.clip_position) >= 0.0)
+}
+
+pub fn needs_clipping(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> bool {
+    !(is_inside(v1) && is_inside(v2) && is_inside(v3))
+}
+
+// Recorta el triángulo contra los planos cercano/lejano (y la banda de guarda) en
+// espacio de recorte. Devuelve un polígono convexo, vacío si queda completamente fuera.
+pub fn clip_triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Vertex> {
+    let mut polygon = vec![v1.clone(), v2.clone(), v3.clone()];
+
+    for plane in PLANES {
+        if polygon.is_empty() {
+            break;
+        }
+
+        let mut clipped = Vec::with_capacity(polygon.len() + 1);
+        for i in 0..polygon.len() {
+            let current = &polygon[i];
+            let next = &polygon[(i + 1) % polygon.len()];
+            let d_current = plane.distance(&current.clip_position);
+            let d_next = plane.distance(&next.clip_position);
+
+            if d_current >= 0.0 {
+                clipped.push(current.clone());
+            }
+            if (d_current >= 0.0) != (d_next >= 0.0) {
+                let t = d_current / (d_current - d_next);
+                clipped.push(lerp_vertex(current, next, t));
+            }
+        }
+
+        polygon = clipped;
+    }
+
+    polygon
+}
+
+fn lerp_vertex(a: &Vertex, b: &Vertex, t: f32) -> Vertex {
+    Vertex {
+        position: a.position + (b.position - a.position) * t,
+        normal: a.normal + (b.normal - a.normal) * t,
+        tex_coords: a.tex_coords + (b.tex_coords - a.tex_coords) * t,
+        color: a.color.lerp(&b.color, t),
+        transformed_position: a.transformed_position + (b.transformed_position - a.transformed_position) * t,
+        transformed_normal: a.transformed_normal + (b.transformed_normal - a.transformed_normal) * t,
+        world_position: a.world_position + (b.world_position - a.world_position) * t,
+        clip_position: a.clip_position + (b.clip_position - a.clip_position) * t,
+        elevation: a.elevation + (b.elevation - a.elevation) * t,
+    }
+}
+
+// División por w y paso a coordenadas de pantalla
+pub fn viewport_transform(vertex: &mut Vertex, viewport_matrix: &Mat4) {
+    let w = vertex.clip_position.w;
+    let ndc = Vec4::new(
+        vertex.clip_position.x / w,
+        vertex.clip_position.y / w,
+        vertex.clip_position.z / w,
+        1.0
+    );
+
+    let screen_position = viewport_matrix * ndc;
+    vertex.transformed_position = Vec3::new(screen_position.x, screen_position.y, screen_position.z);
+}
@@ -0,0 +1,63 @@
+// Reloj de la simulación: separa el tiempo simulado (segundos) de los cuadros
+// dibujados, para que órbitas y shaders no dependan de los FPS.
+pub struct SimulationClock {
+    time: f64,
+    time_scale: f32,
+    paused: bool,
+}
+
+impl SimulationClock {
+    pub fn new() -> Self {
+        SimulationClock {
+            time: 0.0,
+            time_scale: 1.0,
+            paused: false,
+        }
+    }
+
+    pub fn time(&self) -> f32 {
+        self.time as f32
+    }
+
+    pub fn time_scale(&self) -> f32 {
+        self.time_scale
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    // Avanza según el tiempo real transcurrido; no hace nada si está en pausa
+    pub fn advance(&mut self, real_dt: f32) {
+        if !self.paused {
+            self.time += (real_dt * self.time_scale) as f64;
+        }
+    }
+
+    // Avanza un paso aunque esté en pausa, respetando el sentido de time_scale
+    pub fn step(&mut self, dt: f32) {
+        self.time += (dt * self.time_scale.signum()) as f64;
+    }
+
+    pub fn set_time(&mut self, time: f32) {
+        self.time = time as f64;
+    }
+
+    pub fn set_time_scale(&mut self, time_scale: f32) {
+        self.time_scale = time_scale;
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    pub fn reverse(&mut self) {
+        self.time_scale = -self.time_scale;
+    }
+}
+
+impl Default for SimulationClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
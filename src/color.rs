@@ -1,44 +1,125 @@
+use serde::Deserialize;
 use std::fmt;
 
-#[derive(Debug, Clone, Copy)]
+// Componentes en espacio lineal y sin límite superior (HDR): sumar luces no
+// satura. Se pasan a 8 bits sRGB solo al final, con el tone mapping.
+// En la escena se escribe en sRGB de 8 bits, como (r: 255, g: 150, b: 50), con
+// un `a` opcional (0 transparente, 255 opaco, el valor por defecto).
+// El alfa solo lo usan los modos de mezcla; las operaciones aritméticas actúan
+// sobre r, g y b y conservan el alfa del primer operando.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(from = "Srgb8")]
 pub struct Color {
+    r: f32,
+    g: f32,
+    b: f32,
+    a: f32,
+}
+
+#[derive(Deserialize)]
+struct Srgb8 {
     r: u8,
     g: u8,
     b: u8,
+    #[serde(default = "opaque")]
+    a: u8,
+}
+
+fn opaque() -> u8 {
+    255
+}
+
+impl From<Srgb8> for Color {
+    fn from(color: Srgb8) -> Self {
+        Color::new(color.r, color.g, color.b).with_alpha(color.a as f32 / 255.0)
+    }
 }
 
 impl Color {
-    pub const fn new(r: u8, g: u8, b: u8) -> Self {
-        Color { r, g, b }
+    // Color sRGB de 8 bits, como en un selector de colores o una imagen
+    pub fn new(r: u8, g: u8, b: u8) -> Self {
+        Color { r: srgb_to_linear(r), g: srgb_to_linear(g), b: srgb_to_linear(b), a: 1.0 }
+    }
+
+    pub fn from_hex(hex: u32) -> Self {
+        Color::new(((hex >> 16) & 0xFF) as u8, ((hex >> 8) & 0xFF) as u8, (hex & 0xFF) as u8)
     }
 
-    pub const fn from_hex(hex: u32) -> Self {
-        let r = ((hex >> 16) & 0xFF) as u8;
-        let g = ((hex >> 8) & 0xFF) as u8;
-        let b = (hex & 0xFF) as u8;
-        Color { r, g, b }
+    pub const fn from_linear(r: f32, g: f32, b: f32) -> Self {
+        Color { r, g, b, a: 1.0 }
     }
 
     pub const fn black() -> Self {
-        Color { r: 0, g: 0, b: 0 }
+        Color { r: 0.0, g: 0.0, b: 0.0, a: 1.0 }
+    }
+
+    // Mismo color con otra opacidad, en [0, 1]
+    pub fn with_alpha(self, a: f32) -> Self {
+        Color { a: a.clamp(0.0, 1.0), ..self }
+    }
+
+    pub fn r(&self) -> f32 {
+        self.r
+    }
+
+    pub fn g(&self) -> f32 {
+        self.g
+    }
+
+    pub fn b(&self) -> f32 {
+        self.b
     }
 
-    pub fn to_hex(&self) -> u32 {
-        ((self.r as u32) << 16) | ((self.g as u32) << 8) | (self.b as u32)
+    pub fn a(&self) -> f32 {
+        self.a
+    }
+
+    // Componentes lineales en [0, 1], como los colores de un archivo MTL
+    pub fn from_unit(r: f32, g: f32, b: f32) -> Self {
+        Color { r: r.clamp(0.0, 1.0), g: g.clamp(0.0, 1.0), b: b.clamp(0.0, 1.0), a: 1.0 }
+    }
+
+    // sRGB de 8 bits; lo que pase de 1.0 se recorta (para HDR, ver `tonemap`)
+    pub fn to_hex(self) -> u32 {
+        (linear_to_srgb(self.r) << 16) | (linear_to_srgb(self.g) << 8) | linear_to_srgb(self.b)
+    }
+
+    // Luminancia relativa (Rec. 709)
+    pub fn luminance(&self) -> f32 {
+        0.2126 * self.r + 0.7152 * self.g + 0.0722 * self.b
     }
 
     // Linear interpolation between two colors
     pub fn lerp(&self, other: &Color, t: f32) -> Self {
         let t = t.clamp(0.0, 1.0);
         Color {
-            r: (self.r as f32 + (other.r as f32 - self.r as f32) * t).round() as u8,
-            g: (self.g as f32 + (other.g as f32 - self.g as f32) * t).round() as u8,
-            b: (self.b as f32 + (other.b as f32 - self.b as f32) * t).round() as u8,
+            r: self.r + (other.r - self.r) * t,
+            g: self.g + (other.g - self.g) * t,
+            b: self.b + (other.b - self.b) * t,
+            a: self.a + (other.a - self.a) * t,
+        }
+    }
+
+    // Promedio componente a componente, alfa incluido
+    pub fn average(colors: &[Color]) -> Color {
+        if colors.is_empty() {
+            return Color::black();
+        }
+
+        let n = colors.len() as f32;
+        let (mut r, mut g, mut b, mut a) = (0.0, 0.0, 0.0, 0.0);
+        for color in colors {
+            r += color.r;
+            g += color.g;
+            b += color.b;
+            a += color.a;
         }
+
+        Color { r: r / n, g: g / n, b: b / n, a: a / n }
     }
 
     pub fn is_black(&self) -> bool {
-        self.r == 0 && self.g == 0 && self.b == 0 
+        self.r == 0.0 && self.g == 0.0 && self.b == 0.0
     }
 
     pub fn blend_normal(&self, blend: &Color) -> Color {
@@ -46,27 +127,19 @@ impl Color {
       }
     
     pub fn blend_multiply(&self, blend: &Color) -> Color {
-        Color::new(
-            ((self.r as f32 * blend.r as f32) / 255.0) as u8,
-            ((self.g as f32 * blend.g as f32) / 255.0) as u8,
-            ((self.b as f32 * blend.b as f32) / 255.0) as u8
-        )
+        Color { r: self.r * blend.r, g: self.g * blend.g, b: self.b * blend.b, a: self.a }
     }
     
     pub fn blend_add(&self, blend: &Color) -> Color {
-        Color::new(
-            (self.r as u16 + blend.r as u16).min(255) as u8,
-            (self.g as u16 + blend.g as u16).min(255) as u8,
-            (self.b as u16 + blend.b as u16).min(255) as u8
-        )
+        *self + *blend
     }
     
     pub fn blend_subtract(&self, blend: &Color) -> Color {
-        let r = (self.r as i16 - blend.r as i16).max(0).min(255) as u8;
-        let g = (self.g as i16 - blend.g as i16).max(0).min(255) as u8;
-        let b = (self.b as i16 - blend.b as i16).max(0).min(255) as u8;
+        let r = (self.r - blend.r).max(0.0);
+        let g = (self.g - blend.g).max(0.0);
+        let b = (self.b - blend.b).max(0.0);
 
-        Color::new(r, g, b)
+        Color { r, g, b, a: self.a }
     }
 }
 
@@ -77,9 +150,10 @@ impl Add for Color {
 
     fn add(self, other: Color) -> Color {
         Color {
-            r: self.r.saturating_add(other.r),
-            g: self.g.saturating_add(other.g),
-            b: self.b.saturating_add(other.b),
+            r: self.r + other.r,
+            g: self.g + other.g,
+            b: self.b + other.b,
+            a: self.a,
         }
     }
 }
@@ -91,15 +165,28 @@ impl Mul<f32> for Color {
 
     fn mul(self, scalar: f32) -> Color {
         Color {
-            r: (self.r as f32 * scalar).clamp(0.0, 255.0) as u8,
-            g: (self.g as f32 * scalar).clamp(0.0, 255.0) as u8,
-            b: (self.b as f32 * scalar).clamp(0.0, 255.0) as u8,
+            r: (self.r * scalar).max(0.0),
+            g: (self.g * scalar).max(0.0),
+            b: (self.b * scalar).max(0.0),
+            a: self.a,
         }
     }
 }
 
 impl fmt::Display for Color {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        write!(f, "Color(r: {}, g: {}, b: {})", self.r, self.g, self.b)
+        write!(f, "Color(r: {}, g: {}, b: {}, a: {})", self.r, self.g, self.b, self.a)
     }
 }
+
+// Curva sRGB exacta (con el tramo lineal cerca del negro)
+pub fn srgb_to_linear(value: u8) -> f32 {
+    let c = value as f32 / 255.0;
+    if c <= 0.04045 { c / 12.92 } else { ((c + 0.055) / 1.055).powf(2.4) }
+}
+
+pub fn linear_to_srgb(value: f32) -> u32 {
+    let c = value.clamp(0.0, 1.0);
+    let encoded = if c <= 0.003_130_8 { c * 12.92 } else { 1.055 * c.powf(1.0 / 2.4) - 0.055 };
+    (encoded * 255.0).round() as u32
+}
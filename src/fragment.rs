@@ -6,20 +6,33 @@ pub struct Fragment {
     pub position: Vec2,
     pub color: Color,
     pub depth: f32,
+    // Normal y posición en espacio del mundo, para la iluminación
     pub normal: Vec3,
-    pub intensity: f32,
+    pub world_position: Vec3,
+    // Posición en espacio del objeto, para los patrones de los shaders
     pub vertex_position: Vec3,
+    // Cambio de vertex_position y tex_coords al avanzar un píxel en x o en y
+    pub vertex_position_dx: Vec3,
+    pub vertex_position_dy: Vec3,
+    pub tex_coords: Vec2,
+    pub tex_coords_dx: Vec2,
+    pub tex_coords_dy: Vec2,
 }
 
 impl Fragment {
-    pub fn new(x: f32, y: f32, color: Color, depth: f32, normal: Vec3, intensity: f32, vertex_position: Vec3,) -> Self {
+    pub fn new(x: f32, y: f32, color: Color, depth: f32, normal: Vec3, world_position: Vec3, vertex_position: Vec3,) -> Self {
         Fragment {
             position: Vec2::new(x, y),
             color,
             depth,
             normal,
-            intensity,
-            vertex_position
+            world_position,
+            vertex_position,
+            vertex_position_dx: Vec3::new(0.0, 0.0, 0.0),
+            vertex_position_dy: Vec3::new(0.0, 0.0, 0.0),
+            tex_coords: Vec2::new(0.0, 0.0),
+            tex_coords_dx: Vec2::new(0.0, 0.0),
+            tex_coords_dy: Vec2::new(0.0, 0.0),
         }
     }
 }
\ No newline at end of file
@@ -1,10 +1,87 @@
-use rand::Rng;
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::path::Path;
+use crate::bloom::Bloom;
+use crate::color::Color;
+use crate::pipeline::RasterState;
+use crate::tonemap::ToneMapping;
+
+// Cantidades de muestras por píxel soportadas (1 = sin MSAA)
+pub const SAMPLE_COUNTS: [usize; 4] = [1, 2, 4, 8];
+
+// Posiciones de las muestras respecto al centro del píxel, en dieciseisavos de
+// píxel (los patrones estándar de Direct3D)
+const SAMPLES_1: [(i64, i64); 1] = [(0, 0)];
+const SAMPLES_2: [(i64, i64); 2] = [(4, 4), (-4, -4)];
+const SAMPLES_4: [(i64, i64); 4] = [(-2, -6), (6, -2), (-6, 2), (2, 6)];
+const SAMPLES_8: [(i64, i64); 8] = [(1, -3), (-1, 3), (5, 1), (-3, -5), (-5, 5), (-7, -1), (3, 7), (7, -7)];
+
+pub fn sample_offsets(samples: usize) -> &'static [(i64, i64)] {
+    match samples {
+        2 => &SAMPLES_2,
+        4 => &SAMPLES_4,
+        8 => &SAMPLES_8,
+        _ => &SAMPLES_1,
+    }
+}
+
+// Franja de filas completas del framebuffer. Las franjas no se solapan, así que
+// cada una se puede rasterizar en un hilo distinto. Las coordenadas son globales.
+// Guarda color HDR y profundidad por muestra, `samples` por píxel.
+pub struct Tile<'a> {
+    pub y_start: usize,
+    pub width: usize,
+    pub height: usize,
+    pub samples: usize,
+    buffer: &'a mut [Color],
+    zbuffer: &'a mut [f32],
+}
+
+impl Tile<'_> {
+    pub fn y_end(&self) -> usize {
+        self.y_start + self.height
+    }
+
+    fn index(&self, x: usize, y: usize, sample: usize) -> Option<usize> {
+        if x < self.width && y >= self.y_start && y < self.y_end() && sample < self.samples {
+            Some(((y - self.y_start) * self.width + x) * self.samples + sample)
+        } else {
+            None
+        }
+    }
+
+    pub fn depth_test(&self, x: usize, y: usize, sample: usize, depth: f32) -> bool {
+        self.index(x, y, sample).is_some_and(|index| self.zbuffer[index] > depth)
+    }
+
+    // Mezcla el color con el de la muestra según `state.blend` y solo actualiza la
+    // profundidad si `state.depth_write`
+    pub fn point(&mut self, x: usize, y: usize, sample: usize, color: Color, depth: f32, state: &RasterState) {
+        if let Some(index) = self.index(x, y, sample) {
+            if self.zbuffer[index] > depth {
+                self.buffer[index] = state.blend.apply(color, self.buffer[index]);
+                if state.depth_write {
+                    self.zbuffer[index] = depth;
+                }
+            }
+        }
+    }
+}
 
 pub struct Framebuffer {
     pub width: usize,
     pub height: usize,
+    // Imagen final en sRGB de 8 bits; se llena en `resolve`
     pub buffer: Vec<u32>,
+    // Color lineal HDR por muestra, donde se dibuja
+    pub hdr: Vec<Color>,
+    // Una profundidad por muestra
     pub zbuffer: Vec<f32>,
+    samples: usize,
+    tone_mapping: ToneMapping,
+    bloom: Option<Bloom>,
     background_color: u32,
     current_color: u32,
     star_positions: Vec<(usize, usize)>, // Para mantener las posiciones de las estrellas
@@ -16,7 +93,11 @@ impl Framebuffer {
             width,
             height,
             buffer: vec![0; width * height],
+            hdr: vec![Color::black(); width * height],
             zbuffer: vec![f32::INFINITY; width * height],
+            samples: 1,
+            tone_mapping: ToneMapping::default(),
+            bloom: None,
             background_color: 0x000000,
             current_color: 0xFFFFFF,
             star_positions: Vec::new(), // Inicializa el vector vacío
@@ -24,7 +105,12 @@ impl Framebuffer {
     }
 
     pub fn add_stars(&mut self, star_count: usize) {
-        let mut rng = rand::thread_rng();
+        self.add_stars_seeded(star_count, rand::thread_rng().gen());
+    }
+
+    // Misma semilla -> mismo cielo, útil para imágenes de regresión
+    pub fn add_stars_seeded(&mut self, star_count: usize, seed: u64) {
+        let mut rng = StdRng::seed_from_u64(seed);
         let star_color = 0xFFFFFF; // Color blanco para las estrellas
 
         for _ in 0..star_count {
@@ -37,38 +123,137 @@ impl Framebuffer {
         // Dibuja las estrellas en el búfer
         for &(x, y) in &self.star_positions {
             let index = y * self.width + x;
-            self.buffer[index] = star_color; // Coloca las estrellas directamente en el búfer
+            // Coloca las estrellas directamente en el búfer, en todas las muestras del píxel
+            self.hdr[index * self.samples..(index + 1) * self.samples].fill(Color::from_hex(star_color));
         }
     }
 
+    // Framebuffer `factor` veces más grande con el mismo fondo y MSAA; cada estrella
+    // ocupa un bloque de factor x factor para que al reducirlo quede igual
+    pub fn upscaled(&self, factor: usize) -> Framebuffer {
+        let mut framebuffer = Framebuffer::new(self.width * factor, self.height * factor);
+        framebuffer.background_color = self.background_color;
+        framebuffer.current_color = self.current_color;
+        framebuffer.tone_mapping = self.tone_mapping;
+        framebuffer.bloom = self.bloom.map(|bloom| bloom.scaled(factor));
+        framebuffer.star_positions = self
+            .star_positions
+            .iter()
+            .flat_map(|&(x, y)| (0..factor * factor).map(move |i| (x * factor + i % factor, y * factor + i / factor)))
+            .collect();
+        framebuffer.set_samples(self.samples);
+        framebuffer
+    }
+
+    pub fn samples(&self) -> usize {
+        self.samples
+    }
+
+    // Cambia la cantidad de muestras por píxel (MSAA); 1 lo desactiva
+    pub fn set_samples(&mut self, samples: usize) {
+        assert!(SAMPLE_COUNTS.contains(&samples), "MSAA no soporta {} muestras", samples);
+
+        let pixels = self.width * self.height;
+        self.samples = samples;
+        self.zbuffer = vec![f32::INFINITY; pixels * samples];
+        self.hdr = vec![Color::black(); pixels * samples];
+        self.clear();
+    }
+
+    pub fn tone_mapping(&self) -> ToneMapping {
+        self.tone_mapping
+    }
+
+    pub fn set_tone_mapping(&mut self, tone_mapping: ToneMapping) {
+        self.tone_mapping = tone_mapping;
+    }
+
+    pub fn bloom(&self) -> Option<Bloom> {
+        self.bloom
+    }
+
+    // None lo desactiva
+    pub fn set_bloom(&mut self, bloom: Option<Bloom>) {
+        self.bloom = bloom;
+    }
+
     pub fn clear(&mut self) {
         // Limpia el búfer con el color de fondo
-        for pixel in self.buffer.iter_mut() {
-            *pixel = self.background_color;
-        }
-        for depth in self.zbuffer.iter_mut() {
-            *depth = f32::INFINITY;
-        }
+        self.hdr.fill(Color::from_hex(self.background_color));
+        self.clear_depth();
 
         // Redibuja las estrellas después de limpiar el fondo
         let star_color = 0xFFFFFF; // Color blanco para las estrellas
         for &(x, y) in &self.star_positions {
             let index = y * self.width + x;
-            self.buffer[index] = star_color; // Coloca las estrellas nuevamente
+            // Coloca las estrellas nuevamente, en todas las muestras del píxel
+            self.hdr[index * self.samples..(index + 1) * self.samples].fill(Color::from_hex(star_color));
         }
     }
 
+    // Reinicia solo la profundidad, para pasadas que no usan el color (mapas de sombra)
+    pub fn clear_depth(&mut self) {
+        self.zbuffer.fill(f32::INFINITY);
+    }
+
+    // Escribe todas las muestras del píxel que pasen la prueba de profundidad
     pub fn point(&mut self, x: usize, y: usize, depth: f32) {
         if x < self.width && y < self.height {
             let index = y * self.width + x;
 
-            if self.zbuffer[index] > depth {
-                self.buffer[index] = self.current_color;
-                self.zbuffer[index] = depth;
+            for sample in index * self.samples..(index + 1) * self.samples {
+                if self.zbuffer[sample] > depth {
+                    self.hdr[sample] = Color::from_hex(self.current_color);
+                    self.zbuffer[sample] = depth;
+                }
             }
         }
     }
 
+    // Divide el framebuffer en franjas de `rows` filas (la última puede ser más corta)
+    pub fn tiles_mut(&mut self, rows: usize) -> Vec<Tile<'_>> {
+        let (width, samples) = (self.width, self.samples);
+        self.hdr
+            .chunks_mut(width * rows * samples)
+            .zip(self.zbuffer.chunks_mut(width * rows * samples))
+            .enumerate()
+            .map(|(i, (buffer, zbuffer))| Tile {
+                y_start: i * rows,
+                width,
+                height: buffer.len() / (width * samples),
+                samples,
+                buffer,
+                zbuffer,
+            })
+            .collect()
+    }
+
+    // El framebuffer completo como una sola franja
+    pub fn as_tile(&mut self) -> Tile<'_> {
+        Tile {
+            y_start: 0,
+            width: self.width,
+            height: self.height,
+            samples: self.samples,
+            buffer: &mut self.hdr,
+            zbuffer: &mut self.zbuffer,
+        }
+    }
+
+    // Promedia las muestras de cada píxel (en espacio lineal), suma el bloom, aplica
+    // el tone mapping y deja el resultado en `buffer`. Hay que llamarlo antes de
+    // mostrar o guardar la imagen
+    pub fn resolve(&mut self) {
+        let mut pixels: Vec<Color> = self.hdr.chunks_exact(self.samples).map(Color::average).collect();
+        if let Some(bloom) = &self.bloom {
+            bloom.apply(&mut pixels, self.width, self.height);
+        }
+
+        for (pixel, &color) in self.buffer.iter_mut().zip(&pixels) {
+            *pixel = self.tone_mapping.map(color);
+        }
+    }
+
     pub fn set_background_color(&mut self, color: u32) {
         self.background_color = color;
     }
@@ -76,4 +261,43 @@ impl Framebuffer {
     pub fn set_current_color(&mut self, color: u32) {
         self.current_color = color;
     }
-}
\ No newline at end of file
+
+    // Guarda el búfer en disco; el formato se elige según la extensión (.png o .ppm)
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some("png") => self.save_png(path),
+            Some("ppm") => self.save_ppm(path),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("formato de imagen no soportado: {}", path.display()),
+            )),
+        }
+    }
+
+    pub fn save_ppm(&self, path: &Path) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        write!(writer, "P6\n{} {}\n255\n", self.width, self.height)?;
+        writer.write_all(&self.to_rgb_bytes())?;
+        writer.flush()
+    }
+
+    pub fn save_png(&self, path: &Path) -> io::Result<()> {
+        let writer = BufWriter::new(File::create(path)?);
+        let mut encoder = png::Encoder::new(writer, self.width as u32, self.height as u32);
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+
+        let mut png_writer = encoder.write_header().map_err(io::Error::other)?;
+        png_writer.write_image_data(&self.to_rgb_bytes()).map_err(io::Error::other)
+    }
+
+    fn to_rgb_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(self.buffer.len() * 3);
+        for &pixel in &self.buffer {
+            bytes.push(((pixel >> 16) & 0xFF) as u8);
+            bytes.push(((pixel >> 8) & 0xFF) as u8);
+            bytes.push((pixel & 0xFF) as u8);
+        }
+        bytes
+    }
+}
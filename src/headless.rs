@@ -0,0 +1,83 @@
+use std::error::Error;
+use std::path::PathBuf;
+use sr_02_line::clock::SimulationClock;
+use sr_02_line::framebuffer::Framebuffer;
+use sr_02_line::postprocess::PostProcess;
+use sr_02_line::shadow::ShadowMap;
+use crate::solar_system::{FRAMEBUFFER_HEIGHT, FRAMEBUFFER_WIDTH, STAR_COUNT, RenderOptions, initial_camera, load_scene, render_frame};
+
+const STAR_SEED: u64 = 42;
+// Paso fijo por cuadro para que las imágenes no dependan de la velocidad de la máquina
+const FRAME_DT: f32 = 1.0 / 60.0;
+
+#[derive(Clone, Copy)]
+pub enum ImageFormat {
+    Png,
+    Ppm,
+}
+
+impl ImageFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            ImageFormat::Png => "png",
+            ImageFormat::Ppm => "ppm",
+        }
+    }
+}
+
+pub struct HeadlessOptions {
+    pub frames: u32,
+    pub output_dir: PathBuf,
+    pub format: ImageFormat,
+    pub start_time: f32,
+    pub time_scale: f32,
+    pub render: RenderOptions,
+}
+
+impl Default for HeadlessOptions {
+    fn default() -> Self {
+        HeadlessOptions {
+            frames: 1,
+            output_dir: PathBuf::from("frames"),
+            format: ImageFormat::Png,
+            start_time: 0.0,
+            time_scale: 1.0,
+            render: RenderOptions::default(),
+        }
+    }
+}
+
+// Renderiza los cuadros sin abrir ventana y los escribe en options.output_dir
+pub fn run_headless(options: &HeadlessOptions) -> Result<(), Box<dyn Error>> {
+    let mut framebuffer = Framebuffer::new(FRAMEBUFFER_WIDTH, FRAMEBUFFER_HEIGHT);
+    framebuffer.set_background_color(0x000000);
+    options.render.configure(&mut framebuffer);
+    framebuffer.add_stars_seeded(STAR_COUNT, STAR_SEED);
+
+    let camera = initial_camera();
+    let mut scene = load_scene()?;
+    let mut post_process = PostProcess::new(options.render.antialiasing);
+    let mut shadow_map = ShadowMap::new();
+    let mut clock = SimulationClock::new();
+    clock.set_time(options.start_time);
+    clock.set_time_scale(options.time_scale);
+
+    std::fs::create_dir_all(&options.output_dir)?;
+
+    // El primer cuadro es el instante `start_time`; el reloj avanza después de guardar
+    for frame in 1..=options.frames {
+        scene.update(clock.time());
+        render_frame(post_process.target(&mut framebuffer), &mut shadow_map, &camera, &scene, clock.time());
+        post_process.apply(&mut framebuffer);
+
+        let path = options
+            .output_dir
+            .join(format!("frame_{:04}.{}", frame, options.format.extension()));
+        framebuffer.save(&path)?;
+        println!("Guardado {}", path.display());
+
+        clock.advance(FRAME_DT);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,182 @@
+use nalgebra_glm::{Vec3, Mat4, look_at, perspective};
+use std::f32::consts::PI;
+use fastnoise_lite::{FastNoiseLite, NoiseType};
+
+pub mod framebuffer;
+pub mod triangle;
+pub mod vertex;
+pub mod obj;
+pub mod color;
+pub mod fragment;
+pub mod shaders;
+pub mod tiles;
+pub mod camera;
+pub mod clipping;
+pub mod pipeline;
+pub mod clock;
+pub mod orbit;
+pub mod scene;
+pub mod noise;
+pub mod material;
+pub mod texture;
+pub mod light;
+pub mod shadow;
+pub mod postprocess;
+pub mod tonemap;
+pub mod bloom;
+
+pub use framebuffer::{Framebuffer, Tile};
+pub use vertex::Vertex;
+pub use obj::{Mesh, Obj, ObjMaterial};
+pub use camera::Camera;
+pub use color::Color;
+pub use fragment::Fragment;
+pub use triangle::triangle;
+pub use pipeline::{BlendMode, CullMode, FrontFace, RasterState};
+pub use shaders::{vertex_shader, fragment_shader, Shader};
+pub use material::{Material, ShaderRegistry, SurfaceMaterial};
+pub use texture::{FilterMode, Texture, WrapMode};
+pub use light::PointLight;
+pub use shadow::ShadowMap;
+pub use postprocess::{Antialiasing, DownsampleFilter, PostProcess};
+pub use tonemap::{ToneMapper, ToneMapping};
+pub use bloom::Bloom;
+
+use clipping::{clip_triangle, needs_clipping, viewport_transform};
+use triangle::signed_area;
+
+pub struct Uniforms<'a> {
+    pub model_matrix: Mat4,
+    pub view_matrix: Mat4,
+    pub projection_matrix: Mat4,
+    pub viewport_matrix: Mat4,
+    pub time: f32,
+    pub light: PointLight,
+    pub camera_position: Vec3,
+    pub noise: &'a FastNoiseLite,
+    pub shader: &'a dyn Shader,
+    pub texture: Option<&'a Texture>,
+    // Material MTL de la submalla que se está dibujando y su mapa de normales
+    pub surface: Option<&'a SurfaceMaterial>,
+    pub normal_map: Option<&'a Texture>,
+    // Mapa de sombras de `light`; sin él todo queda iluminado
+    pub shadow: Option<&'a ShadowMap>,
+}
+
+
+pub fn create_noise() -> FastNoiseLite {
+    let mut noise = FastNoiseLite::with_seed(1337);
+    noise.set_noise_type(Some(NoiseType::OpenSimplex2));
+    noise
+}
+
+pub fn create_model_matrix(translation: Vec3, scale: f32, rotation: Vec3) -> Mat4 {
+    let (sin_x, cos_x) = rotation.x.sin_cos();
+    let (sin_y, cos_y) = rotation.y.sin_cos();
+    let (sin_z, cos_z) = rotation.z.sin_cos();
+
+    let rotation_matrix_x = Mat4::new(
+        1.0,  0.0,    0.0,   0.0,
+        0.0,  cos_x, -sin_x, 0.0,
+        0.0,  sin_x,  cos_x, 0.0,
+        0.0,  0.0,    0.0,   1.0,
+    );
+
+    let rotation_matrix_y = Mat4::new(
+        cos_y,  0.0,  sin_y, 0.0,
+        0.0,    1.0,  0.0,   0.0,
+        -sin_y, 0.0,  cos_y, 0.0,
+        0.0,    0.0,  0.0,   1.0,
+    );
+
+    let rotation_matrix_z = Mat4::new(
+        cos_z, -sin_z, 0.0, 0.0,
+        sin_z,  cos_z, 0.0, 0.0,
+        0.0,    0.0,  1.0, 0.0,
+        0.0,    0.0,  0.0, 1.0,
+    );
+
+    let rotation_matrix = rotation_matrix_z * rotation_matrix_y * rotation_matrix_x;
+
+    let transform_matrix = Mat4::new(
+        scale, 0.0,   0.0,   translation.x,
+        0.0,   scale, 0.0,   translation.y,
+        0.0,   0.0,   scale, translation.z,
+        0.0,   0.0,   0.0,   1.0,
+    );
+
+    transform_matrix * rotation_matrix
+}
+
+pub fn create_view_matrix(eye: Vec3, center: Vec3, up: Vec3) -> Mat4 {
+    look_at(&eye, &center, &up)
+}
+
+pub fn create_perspective_matrix(window_width: f32, window_height: f32) -> Mat4 {
+    let fov = 45.0 * PI / 180.0;
+    let aspect_ratio = window_width / window_height;
+    let near = 0.1;
+    let far = 1000.0;
+
+    perspective(fov, aspect_ratio, near, far)
+}
+
+pub fn create_viewport_matrix(width: f32, height: f32) -> Mat4 {
+    Mat4::new(
+        width / 2.0, 0.0, 0.0, width / 2.0,
+        0.0, -height / 2.0, 0.0, height / 2.0,
+        0.0, 0.0, 1.0, 0.0,
+        0.0, 0.0, 0.0, 1.0
+    )
+}
+
+pub fn render(framebuffer: &mut Framebuffer, uniforms: &Uniforms, raster_state: &RasterState, mesh: &Mesh) {
+    draw_mesh(framebuffer, uniforms, raster_state, mesh, |fragment| fragment_shader(fragment, uniforms));
+}
+
+// Como `render`, pero con cualquier función de sombreado (p. ej. solo profundidad
+// para los mapas de sombra)
+pub fn draw_mesh<F>(framebuffer: &mut Framebuffer, uniforms: &Uniforms, raster_state: &RasterState, mesh: &Mesh, shade: F)
+where
+    F: Fn(&Fragment) -> Color + Sync,
+{
+    // Cada vértice único se transforma una sola vez por dibujo. El recorte solo lee
+    // los campos en espacio de recorte, así que la transformación de viewport se
+    // aplica sobre los mismos vértices
+    let mut vertices: Vec<Vertex> = (0..mesh.vertex_count())
+        .map(|index| vertex_shader(&mesh.vertex(index), uniforms))
+        .collect();
+    for vertex in &mut vertices {
+        viewport_transform(vertex, &uniforms.viewport_matrix);
+    }
+
+    // Los vértices de los polígonos recortados se agregan al final
+    let mut clipped_vertices = Vec::new();
+    let mut triangles = Vec::with_capacity(mesh.triangle_count());
+    for face in mesh.indices.chunks_exact(3) {
+        let (a, b, c) = (face[0] as usize, face[1] as usize, face[2] as usize);
+        let (va, vb, vc) = (&vertices[a], &vertices[b], &vertices[c]);
+
+        if needs_clipping(va, vb, vc) {
+            // El polígono recortado se vuelve a triangular en abanico
+            let first = vertices.len() + clipped_vertices.len();
+            for mut vertex in clip_triangle(va, vb, vc) {
+                viewport_transform(&mut vertex, &uniforms.viewport_matrix);
+                clipped_vertices.push(vertex);
+            }
+            let end = vertices.len() + clipped_vertices.len();
+            for j in first + 1..end.saturating_sub(1) {
+                triangles.push([first, j, j + 1]);
+            }
+        } else {
+            triangles.push([a, b, c]);
+        }
+    }
+    vertices.append(&mut clipped_vertices);
+
+    triangles.retain(|&[a, b, c]| {
+        !raster_state.is_culled(signed_area(&vertices[a], &vertices[b], &vertices[c]))
+    });
+
+    tiles::rasterize(framebuffer, raster_state, &vertices, &triangles, shade);
+}
@@ -0,0 +1,61 @@
+use nalgebra_glm::{Vec3, dot};
+use serde::Deserialize;
+use crate::color::Color;
+
+// Luz puntual en espacio del mundo. No se atenúa con la distancia: las distancias
+// de la escena no son físicas y con atenuación los planetas lejanos quedarían negros.
+#[derive(Clone, Copy, Debug)]
+pub struct PointLight {
+    pub position: Vec3,
+    pub color: Color,
+    pub intensity: f32,
+    // Luz mínima para que el lado nocturno no sea negro puro
+    pub ambient: f32,
+}
+
+impl PointLight {
+    pub fn new(position: Vec3, color: Color, intensity: f32, ambient: f32) -> Self {
+        PointLight { position, color, intensity, ambient }
+    }
+
+    // Términos difuso y especular de Blinn-Phong para un punto con normal `normal`
+    // visto desde `eye`
+    pub fn blinn_phong(&self, position: Vec3, normal: Vec3, eye: Vec3, shininess: f32) -> (f32, f32) {
+        let to_light = (self.position - position).normalize();
+        let to_eye = (eye - position).normalize();
+        let normal = normal.normalize();
+
+        let diffuse = dot(&normal, &to_light).max(0.0);
+        if diffuse == 0.0 {
+            return (0.0, 0.0);
+        }
+
+        let halfway = (to_light + to_eye).normalize();
+        let specular = dot(&normal, &halfway).max(0.0).powf(shininess);
+
+        (diffuse * self.intensity, specular * self.intensity)
+    }
+}
+
+// Luz que emite un cuerpo de la escena desde su posición
+#[derive(Deserialize)]
+pub struct LightDescription {
+    #[serde(default = "default_light_color")]
+    pub color: Color,
+    #[serde(default = "default_light_intensity")]
+    pub intensity: f32,
+    #[serde(default = "default_ambient")]
+    pub ambient: f32,
+}
+
+fn default_light_color() -> Color {
+    Color::new(255, 255, 255)
+}
+
+fn default_light_intensity() -> f32 {
+    1.0
+}
+
+fn default_ambient() -> f32 {
+    0.05
+}
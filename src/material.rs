@@ -0,0 +1,114 @@
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use std::collections::HashMap;
+use crate::color::Color;
+use crate::noise::NoiseHandle;
+use crate::obj::ObjMaterial;
+use crate::shaders::{
+    AtmosphereShader, EarthShader, GasShader, MoonShader, RockyShader, Shader, ShipShader, StarShader, SunShader, TextureShader,
+    MtlShader,
+};
+
+// Material tal como aparece en la escena: el nombre del shader, sus parámetros
+// (los que falten toman su valor por defecto) y el generador de ruido que usa
+#[derive(Deserialize)]
+pub struct MaterialDescription {
+    pub shader: String,
+    #[serde(default)]
+    pub params: Option<ron::Value>,
+    // Nombre de un generador de `noises`; si falta se usa el de por defecto
+    #[serde(default)]
+    pub noise: Option<String>,
+    // Nombre de una textura de `textures`
+    #[serde(default)]
+    pub texture: Option<String>,
+}
+
+pub struct Material {
+    pub shader: Box<dyn Shader>,
+    pub noise: NoiseHandle,
+    // Índice en `Scene::textures`
+    pub texture: Option<usize>,
+}
+
+// Material MTL de una submalla, con sus mapas ya cargados en `Scene::textures`
+pub struct SurfaceMaterial {
+    pub name: String,
+    pub ambient: Color,
+    // Kd, con la opacidad `d` del MTL como alfa
+    pub diffuse: Color,
+    pub specular: Color,
+    pub shininess: f32,
+    pub diffuse_map: Option<usize>,
+    pub normal_map: Option<usize>,
+}
+
+impl SurfaceMaterial {
+    pub fn new(material: &ObjMaterial, diffuse_map: Option<usize>, normal_map: Option<usize>) -> Self {
+        let color = |c: nalgebra_glm::Vec3| Color::from_unit(c.x, c.y, c.z);
+
+        SurfaceMaterial {
+            name: material.name.clone(),
+            ambient: color(material.ambient),
+            diffuse: color(material.diffuse).with_alpha(material.dissolve),
+            specular: color(material.specular),
+            shininess: material.shininess,
+            diffuse_map,
+            normal_map,
+        }
+    }
+}
+
+pub type ShaderFactory = fn(ron::Value) -> Result<Box<dyn Shader>, ron::Error>;
+
+fn build_shader<S>(params: ron::Value) -> Result<Box<dyn Shader>, ron::Error>
+where
+    S: Shader + DeserializeOwned + 'static,
+{
+    Ok(Box::new(params.into_rust::<S>()?))
+}
+
+// Shaders disponibles por nombre para las escenas
+pub struct ShaderRegistry {
+    factories: HashMap<String, ShaderFactory>,
+}
+
+impl ShaderRegistry {
+    // Incluye los shaders del sistema solar
+    pub fn new() -> Self {
+        let mut registry = ShaderRegistry {
+            factories: HashMap::new(),
+        };
+        registry.register::<EarthShader>("earth");
+        registry.register::<MoonShader>("moon");
+        registry.register::<SunShader>("sun");
+        registry.register::<GasShader>("gas");
+        registry.register::<RockyShader>("rocky");
+        registry.register::<ShipShader>("ship");
+        registry.register::<StarShader>("star");
+        registry.register::<TextureShader>("texture");
+        registry.register::<MtlShader>("mtl");
+        registry.register::<AtmosphereShader>("atmosphere");
+        registry
+    }
+
+    // Los parámetros del shader se leen de la escena con serde
+    pub fn register<S>(&mut self, name: &str)
+    where
+        S: Shader + DeserializeOwned + 'static,
+    {
+        self.factories.insert(name.to_string(), build_shader::<S>);
+    }
+
+    // None si no hay un shader con ese nombre
+    pub fn create(&self, name: &str, params: Option<ron::Value>) -> Option<Result<Box<dyn Shader>, ron::Error>> {
+        let params = params.unwrap_or_else(|| ron::Value::Map(ron::Map::new()));
+        self.factories.get(name).map(|factory| factory(params))
+    }
+}
+
+impl Default for ShaderRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
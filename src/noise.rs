@@ -0,0 +1,139 @@
+use fastnoise_lite::{FastNoiseLite, FractalType, NoiseType};
+use serde::Deserialize;
+use std::collections::HashMap;
+use crate::create_noise;
+
+// Nombre del generador que usan los cuerpos que no piden uno
+pub const DEFAULT_NOISE: &str = "default";
+
+#[derive(Deserialize, Clone, Copy, Debug)]
+pub enum NoiseKind {
+    OpenSimplex2,
+    OpenSimplex2S,
+    Cellular,
+    Perlin,
+    ValueCubic,
+    Value,
+}
+
+impl From<NoiseKind> for NoiseType {
+    fn from(kind: NoiseKind) -> Self {
+        match kind {
+            NoiseKind::OpenSimplex2 => NoiseType::OpenSimplex2,
+            NoiseKind::OpenSimplex2S => NoiseType::OpenSimplex2S,
+            NoiseKind::Cellular => NoiseType::Cellular,
+            NoiseKind::Perlin => NoiseType::Perlin,
+            NoiseKind::ValueCubic => NoiseType::ValueCubic,
+            NoiseKind::Value => NoiseType::Value,
+        }
+    }
+}
+
+// Generador de ruido declarado en la escena. Los valores por defecto coinciden
+// con `create_noise()`.
+#[derive(Deserialize)]
+pub struct NoiseDescription {
+    pub name: String,
+    #[serde(default = "default_noise_type")]
+    pub noise_type: NoiseKind,
+    #[serde(default = "default_seed")]
+    pub seed: i32,
+    #[serde(default = "default_frequency")]
+    pub frequency: f32,
+    // Con octavas se suma ruido fractal (FBm); sin ellas es una sola capa
+    #[serde(default)]
+    pub octaves: Option<i32>,
+    #[serde(default = "default_lacunarity")]
+    pub lacunarity: f32,
+    #[serde(default = "default_gain")]
+    pub gain: f32,
+}
+
+impl NoiseDescription {
+    pub fn build(&self) -> FastNoiseLite {
+        let mut noise = FastNoiseLite::with_seed(self.seed);
+        noise.set_noise_type(Some(self.noise_type.into()));
+        noise.set_frequency(Some(self.frequency));
+
+        if let Some(octaves) = self.octaves {
+            noise.set_fractal_type(Some(FractalType::FBm));
+            noise.set_fractal_octaves(Some(octaves));
+            noise.set_fractal_lacunarity(Some(self.lacunarity));
+            noise.set_fractal_gain(Some(self.gain));
+        }
+
+        noise
+    }
+}
+
+fn default_noise_type() -> NoiseKind {
+    NoiseKind::OpenSimplex2
+}
+
+fn default_seed() -> i32 {
+    1337
+}
+
+fn default_frequency() -> f32 {
+    0.01
+}
+
+fn default_lacunarity() -> f32 {
+    2.0
+}
+
+fn default_gain() -> f32 {
+    0.5
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NoiseHandle(usize);
+
+// Generadores de ruido creados una sola vez y compartidos por nombre
+pub struct NoiseRegistry {
+    generators: Vec<FastNoiseLite>,
+    names: HashMap<String, NoiseHandle>,
+}
+
+impl NoiseRegistry {
+    // Siempre incluye el generador DEFAULT_NOISE
+    pub fn new() -> Self {
+        let mut registry = NoiseRegistry {
+            generators: Vec::new(),
+            names: HashMap::new(),
+        };
+        registry.insert(DEFAULT_NOISE, create_noise());
+        registry
+    }
+
+    // Registra un generador; si el nombre ya existía lo reemplaza y conserva el handle
+    pub fn insert(&mut self, name: &str, noise: FastNoiseLite) -> NoiseHandle {
+        if let Some(&handle) = self.names.get(name) {
+            self.generators[handle.0] = noise;
+            return handle;
+        }
+
+        let handle = NoiseHandle(self.generators.len());
+        self.generators.push(noise);
+        self.names.insert(name.to_string(), handle);
+        handle
+    }
+
+    pub fn handle(&self, name: &str) -> Option<NoiseHandle> {
+        self.names.get(name).copied()
+    }
+
+    pub fn default_handle(&self) -> NoiseHandle {
+        self.names[DEFAULT_NOISE]
+    }
+
+    pub fn get(&self, handle: NoiseHandle) -> &FastNoiseLite {
+        &self.generators[handle.0]
+    }
+}
+
+impl Default for NoiseRegistry {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -1,43 +1,129 @@
-use tobj;
 use nalgebra_glm::{Vec2, Vec3};
+use std::path::Path;
 use crate::vertex::Vertex;
 
 pub struct Obj {
     meshes: Vec<Mesh>,
+    materials: Vec<ObjMaterial>,
 }
 
-struct Mesh {
-    vertices: Vec<Vec3>,
-    normals: Vec<Vec3>,
-    texcoords: Vec<Vec2>,
-    indices: Vec<u32>,
+// Material de un archivo MTL. Los valores que falten toman los de Blender por
+// defecto; las rutas de los mapas ya son relativas al directorio de trabajo.
+#[derive(Clone, Debug)]
+pub struct ObjMaterial {
+    pub name: String,
+    pub ambient: Vec3,
+    pub diffuse: Vec3,
+    pub specular: Vec3,
+    pub shininess: f32,
+    // Opacidad `d` (1 = opaco)
+    pub dissolve: f32,
+    pub diffuse_map: Option<String>,
+    pub normal_map: Option<String>,
+}
+
+impl ObjMaterial {
+    fn from_mtl(material: tobj::Material, directory: &Path) -> Self {
+        let color = |c: Option<[f32; 3]>, default: f32| c.map(Vec3::from).unwrap_or(Vec3::new(default, default, default));
+        let map_path = |path: Option<String>| path.map(|path| directory.join(path).to_string_lossy().into_owned());
+
+        ObjMaterial {
+            name: material.name,
+            ambient: color(material.ambient, 1.0),
+            diffuse: color(material.diffuse, 0.8),
+            specular: color(material.specular, 0.5),
+            shininess: material.shininess.unwrap_or(250.0),
+            dissolve: material.dissolve.unwrap_or(1.0),
+            diffuse_map: map_path(material.diffuse_texture),
+            normal_map: map_path(material.normal_texture),
+        }
+    }
+}
+
+// Malla indexada: normals y texcoords tienen siempre un elemento por posición,
+// así que un mismo índice sirve para los tres arreglos
+pub struct Mesh {
+    pub positions: Vec<Vec3>,
+    pub normals: Vec<Vec3>,
+    pub texcoords: Vec<Vec2>,
+    pub indices: Vec<u32>,
+    // Índice en `Obj::materials()` según el `usemtl` de la submalla
+    pub material: Option<usize>,
+}
+
+impl Mesh {
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    pub fn vertex(&self, index: usize) -> Vertex {
+        let position = self.positions[index];
+
+        // Calcular la elevación (puedes personalizar este cálculo)
+        let elevation = position.y; // Por ejemplo, usar la componente y como elevación
+
+        Vertex::new(position, self.normals[index], self.texcoords[index], elevation)
+    }
 }
 
 impl Obj {
     pub fn load(filename: &str) -> Result<Self, tobj::LoadError> {
-        let (models, _) = tobj::load_obj(filename, &tobj::LoadOptions {
+        let (models, materials) = tobj::load_obj(filename, &tobj::LoadOptions {
             single_index: true,
             triangulate: true,
             ..Default::default()
         })?;
 
+        // Un `mtllib` que no existe no impide usar la geometría: las submallas se
+        // quedan sin material
+        let directory = Path::new(filename).parent().unwrap_or(Path::new(""));
+        let materials = materials
+            .map(|materials| materials.into_iter().map(|m| ObjMaterial::from_mtl(m, directory)).collect())
+            .unwrap_or_default();
+
         let meshes = models.into_iter().map(|model| {
             let mesh = model.mesh;
+            let vertex_count = mesh.positions.len() / 3;
+
+            let mut normals: Vec<Vec3> = mesh.normals.chunks(3)
+                .map(|n| Vec3::new(n[0], n[1], n[2]))
+                .collect();
+            normals.resize(vertex_count, Vec3::new(0.0, 1.0, 0.0));
+
+            let mut texcoords: Vec<Vec2> = mesh.texcoords.chunks(2)
+                .map(|t| Vec2::new(t[0], 1.0 - t[1]))
+                .collect();
+            texcoords.resize(vertex_count, Vec2::new(0.0, 0.0));
+
             Mesh {
-                vertices: mesh.positions.chunks(3)
+                positions: mesh.positions.chunks(3)
                     .map(|v| Vec3::new(v[0], v[1], v[2]))
                     .collect(),
-                normals: mesh.normals.chunks(3)
-                    .map(|n| Vec3::new(n[0], n[1], n[2]))
-                    .collect(),
-                texcoords: mesh.texcoords.chunks(2)
-                    .map(|t| Vec2::new(t[0], 1.0 - t[1]))
-                    .collect(),
+                normals,
+                texcoords,
                 indices: mesh.indices,
+                material: mesh.material_id,
             }
         }).collect();
 
-        Ok(Obj { meshes })
+        Ok(Obj { meshes, materials })
+    }
+
+    pub fn meshes(&self) -> &[Mesh] {
+        &self.meshes
+    }
+
+    pub fn materials(&self) -> &[ObjMaterial] {
+        &self.materials
+    }
+
+    // Separa las submallas de sus materiales
+    pub fn into_parts(self) -> (Vec<Mesh>, Vec<ObjMaterial>) {
+        (self.meshes, self.materials)
     }
 
     pub fn get_vertex_array(&self) -> Vec<Vertex> {
@@ -45,18 +131,7 @@ impl Obj {
 
         for mesh in &self.meshes {
             for &index in &mesh.indices {
-                let position = mesh.vertices[index as usize];
-                let normal = mesh.normals.get(index as usize)
-                    .cloned()
-                    .unwrap_or(Vec3::new(0.0, 1.0, 0.0));
-                let tex_coords = mesh.texcoords.get(index as usize)
-                    .cloned()
-                    .unwrap_or(Vec2::new(0.0, 0.0));
-
-                // Calcular la elevación (puedes personalizar este cálculo)
-                let elevation = position.y; // Por ejemplo, usar la componente y como elevación
-
-                vertices.push(Vertex::new(position, normal, tex_coords, elevation));
+                vertices.push(mesh.vertex(index as usize));
             }
         }
 
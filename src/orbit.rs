@@ -0,0 +1,144 @@
+use nalgebra_glm::Vec3;
+use std::f32::consts::PI;
+
+const KEPLER_TOLERANCE: f32 = 1e-6;
+const KEPLER_MAX_ITERATIONS: usize = 16;
+
+// Elementos orbitales clásicos. Los ángulos van en radianes y el plano de
+// referencia es XZ (Y hacia arriba), igual que el resto de la escena.
+#[derive(Clone, Copy, Debug)]
+pub struct OrbitalElements {
+    pub semi_major_axis: f32,
+    pub eccentricity: f32,
+    pub inclination: f32,
+    pub longitude_of_ascending_node: f32,
+    pub argument_of_periapsis: f32,
+    // Anomalía media en t = 0
+    pub mean_anomaly: f32,
+    pub period: f32,
+}
+
+impl OrbitalElements {
+    pub fn circular(radius: f32, period: f32) -> Self {
+        OrbitalElements {
+            semi_major_axis: radius,
+            eccentricity: 0.0,
+            inclination: 0.0,
+            longitude_of_ascending_node: 0.0,
+            argument_of_periapsis: 0.0,
+            mean_anomaly: 0.0,
+            period,
+        }
+    }
+
+    pub fn mean_motion(&self) -> f32 {
+        2.0 * PI / self.period
+    }
+
+    pub fn mean_anomaly_at(&self, time: f32) -> f32 {
+        let m = self.mean_anomaly + self.mean_motion() * time;
+        // Normaliza a [-PI, PI) para que Newton converja rápido
+        (m + PI).rem_euclid(2.0 * PI) - PI
+    }
+
+    // Resuelve la ecuación de Kepler M = E - e sin(E) por Newton-Raphson
+    pub fn eccentric_anomaly(&self, mean_anomaly: f32) -> f32 {
+        let e = self.eccentricity;
+        let mut eccentric = if e < 0.8 { mean_anomaly } else { PI.copysign(mean_anomaly) };
+
+        for _ in 0..KEPLER_MAX_ITERATIONS {
+            let delta = (eccentric - e * eccentric.sin() - mean_anomaly) / (1.0 - e * eccentric.cos());
+            eccentric -= delta;
+            if delta.abs() < KEPLER_TOLERANCE {
+                break;
+            }
+        }
+
+        eccentric
+    }
+
+    // Posición relativa al cuerpo central en el instante `time`
+    pub fn position_at(&self, time: f32) -> Vec3 {
+        let e = self.eccentricity;
+        let a = self.semi_major_axis;
+        let eccentric = self.eccentric_anomaly(self.mean_anomaly_at(time));
+
+        // Coordenadas en el plano de la órbita, con el periapsis sobre el eje x
+        let x = a * (eccentric.cos() - e);
+        let y = a * (1.0 - e * e).sqrt() * eccentric.sin();
+
+        let (sin_w, cos_w) = self.argument_of_periapsis.sin_cos();
+        let (sin_i, cos_i) = self.inclination.sin_cos();
+        let (sin_o, cos_o) = self.longitude_of_ascending_node.sin_cos();
+
+        // Rz(Ω) * Rx(i) * Rz(ω) aplicado a (x, y, 0)
+        let px = x * cos_w - y * sin_w;
+        let py = x * sin_w + y * cos_w;
+
+        let qx = px;
+        let qy = py * cos_i;
+        let qz = py * sin_i;
+
+        let rx = qx * cos_o - qy * sin_o;
+        let ry = qx * sin_o + qy * cos_o;
+        let rz = qz;
+
+        // El eje z "hacia arriba" de la convención astronómica es nuestro eje y
+        Vec3::new(rx, rz, ry)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tilted(eccentricity: f32) -> OrbitalElements {
+        OrbitalElements {
+            semi_major_axis: 4.0,
+            eccentricity,
+            inclination: 0.3,
+            longitude_of_ascending_node: 1.1,
+            argument_of_periapsis: 2.0,
+            mean_anomaly: 0.0,
+            period: 10.0,
+        }
+    }
+
+    #[test]
+    fn circular_orbit_keeps_radius() {
+        let orbit = OrbitalElements::circular(3.0, 7.0);
+        for i in 0..50 {
+            let position = orbit.position_at(i as f32 * 0.37);
+            assert!((position.norm() - 3.0).abs() < 1e-4, "radio {} en t = {}", position.norm(), i as f32 * 0.37);
+            assert!(position.y.abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn periapsis_distance_at_zero_mean_anomaly() {
+        let orbit = tilted(0.6);
+        let distance = orbit.position_at(0.0).norm();
+        assert!((distance - 4.0 * (1.0 - 0.6)).abs() < 1e-4, "distancia {}", distance);
+    }
+
+    #[test]
+    fn position_repeats_after_one_period() {
+        let orbit = tilted(0.4);
+        for i in 0..20 {
+            let time = i as f32 * 0.73;
+            let difference = (orbit.position_at(time + orbit.period) - orbit.position_at(time)).norm();
+            assert!(difference < 1e-3, "diferencia {} en t = {}", difference, time);
+        }
+    }
+
+    #[test]
+    fn solver_converges_for_high_eccentricity() {
+        let orbit = tilted(0.95);
+        for i in 0..200 {
+            let mean_anomaly = -PI + 2.0 * PI * i as f32 / 200.0;
+            let eccentric = orbit.eccentric_anomaly(mean_anomaly);
+            let residual = eccentric - 0.95 * eccentric.sin() - mean_anomaly;
+            assert!(residual.abs() < 1e-5, "residuo {} con M = {}", residual, mean_anomaly);
+        }
+    }
+}
@@ -0,0 +1,92 @@
+use serde::Deserialize;
+use crate::color::Color;
+
+// Qué caras se descartan antes de rasterizar
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+pub enum CullMode {
+    #[default]
+    Back,
+    Front,
+    None,
+}
+
+// Orden de los vértices de una cara frontal, visto desde la cámara
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+pub enum FrontFace {
+    #[default]
+    Ccw,
+    Cw,
+}
+
+// Cómo se combina el color del fragmento (src) con el del framebuffer (dst).
+// El alfa de src escala la contribución en todos los modos salvo Replace
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+pub enum BlendMode {
+    // Sobrescribe, ignorando el alfa
+    #[default]
+    Replace,
+    // src * a + dst * (1 - a)
+    Alpha,
+    // dst + src * a, para brillos y halos
+    Additive,
+    // dst * src, mezclado con dst según a; oscurece, como un filtro de color
+    Multiply,
+}
+
+impl BlendMode {
+    pub fn is_opaque(self) -> bool {
+        self == BlendMode::Replace
+    }
+
+    pub fn apply(self, src: Color, dst: Color) -> Color {
+        let alpha = src.a();
+        match self {
+            BlendMode::Replace => src,
+            BlendMode::Alpha => dst.lerp(&src, alpha),
+            BlendMode::Additive => dst.blend_add(&(src * alpha)),
+            BlendMode::Multiply => dst.lerp(&dst.blend_multiply(&src), alpha),
+        }
+    }
+}
+
+// Estado fijo del rasterizador para una llamada a render()
+#[derive(Clone, Copy, Debug)]
+pub struct RasterState {
+    pub cull_mode: CullMode,
+    pub front_face: FrontFace,
+    pub blend: BlendMode,
+    // false: el fragmento se prueba contra el z-buffer pero no lo actualiza, para que
+    // las superficies transparentes no oculten lo que se dibuja detrás después
+    pub depth_write: bool,
+}
+
+impl Default for RasterState {
+    fn default() -> Self {
+        RasterState::new(CullMode::default(), FrontFace::default())
+    }
+}
+
+impl RasterState {
+    pub fn new(cull_mode: CullMode, front_face: FrontFace) -> Self {
+        RasterState { cull_mode, front_face, blend: BlendMode::Replace, depth_write: true }
+    }
+
+    // `signed_area` es el área en pantalla que da triangle::signed_area:
+    // positiva para triángulos antihorarios en NDC
+    pub fn is_culled(&self, signed_area: f32) -> bool {
+        if signed_area == 0.0 {
+            return true;
+        }
+
+        let is_front = match self.front_face {
+            FrontFace::Ccw => signed_area > 0.0,
+            FrontFace::Cw => signed_area < 0.0,
+        };
+
+        match self.cull_mode {
+            CullMode::Back => !is_front,
+            CullMode::Front => is_front,
+            CullMode::None => false,
+        }
+    }
+}
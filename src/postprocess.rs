@@ -0,0 +1,266 @@
+use std::f32::consts::PI;
+use std::fmt;
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+
+// Lóbulos del filtro de Lanczos
+const LANCZOS_LOBES: f32 = 2.0;
+
+// FXAA: contraste mínimo (absoluto y relativo al máximo local) para tratar un píxel como borde
+const FXAA_EDGE_THRESHOLD: f32 = 0.125;
+const FXAA_EDGE_THRESHOLD_MIN: f32 = 0.0312;
+// Cuánto se suaviza el aliasing dentro de un píxel (0 lo desactiva)
+const FXAA_SUBPIXEL_QUALITY: f32 = 0.75;
+// Pasos con los que se recorre el borde en cada sentido buscando su final
+const FXAA_STEPS: [usize; 11] = [1, 1, 1, 1, 1, 2, 2, 2, 2, 4, 8];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DownsampleFilter {
+    Box,
+    Lanczos,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Antialiasing {
+    None,
+    // Dibuja a `factor` veces la resolución y reduce con `filter`
+    Supersample { factor: usize, filter: DownsampleFilter },
+    // Filtro sobre la imagen final, sin cambiar la resolución
+    Fxaa,
+}
+
+impl fmt::Display for Antialiasing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Antialiasing::None => write!(f, "sin AA"),
+            Antialiasing::Supersample { factor, filter: DownsampleFilter::Box } => write!(f, "SSAA {}x box", factor),
+            Antialiasing::Supersample { factor, filter: DownsampleFilter::Lanczos } => write!(f, "SSAA {}x Lanczos", factor),
+            Antialiasing::Fxaa => write!(f, "FXAA"),
+        }
+    }
+}
+
+// Etapa de posprocesado entre `render_frame` y la imagen que se muestra o guarda:
+//   render_frame(post.target(&mut framebuffer), ..);
+//   post.apply(&mut framebuffer);
+pub struct PostProcess {
+    antialiasing: Antialiasing,
+    // Destino en alta resolución del supermuestreo
+    supersampled: Option<Framebuffer>,
+}
+
+impl PostProcess {
+    pub fn new(antialiasing: Antialiasing) -> Self {
+        PostProcess { antialiasing, supersampled: None }
+    }
+
+    pub fn antialiasing(&self) -> Antialiasing {
+        self.antialiasing
+    }
+
+    pub fn set_antialiasing(&mut self, antialiasing: Antialiasing) {
+        self.antialiasing = antialiasing;
+        self.supersampled = None;
+    }
+
+    // Framebuffer en el que hay que dibujar el cuadro: el propio `framebuffer` o
+    // uno más grande si hay supermuestreo
+    pub fn target<'a>(&'a mut self, framebuffer: &'a mut Framebuffer) -> &'a mut Framebuffer {
+        let Antialiasing::Supersample { factor, .. } = self.antialiasing else {
+            return framebuffer;
+        };
+
+        // Se recrea si cambió el tamaño o el MSAA de la salida
+        let stale = self.supersampled.as_ref().is_none_or(|target| {
+            target.width != framebuffer.width * factor || target.samples() != framebuffer.samples()
+        });
+        if stale {
+            self.supersampled = Some(framebuffer.upscaled(factor));
+        }
+
+        // El tone mapping y el bloom pueden cambiar en cualquier cuadro (teclas T, [, ] y B)
+        let target = self.supersampled.as_mut().unwrap();
+        target.set_tone_mapping(framebuffer.tone_mapping());
+        target.set_bloom(framebuffer.bloom().map(|bloom| bloom.scaled(factor)));
+        target
+    }
+
+    // Deja en `framebuffer.buffer` la imagen final
+    pub fn apply(&mut self, framebuffer: &mut Framebuffer) {
+        match (self.antialiasing, &self.supersampled) {
+            (Antialiasing::Supersample { factor, filter }, Some(source)) => match filter {
+                DownsampleFilter::Box => downsample_box(source, framebuffer, factor),
+                DownsampleFilter::Lanczos => downsample_lanczos(source, framebuffer, factor),
+            },
+            (Antialiasing::Fxaa, _) => fxaa(framebuffer),
+            _ => {}
+        }
+    }
+}
+
+// Promedio de cada bloque de factor x factor
+pub fn downsample_box(source: &Framebuffer, target: &mut Framebuffer, factor: usize) {
+    let mut block = Vec::with_capacity(factor * factor);
+
+    for y in 0..target.height {
+        for x in 0..target.width {
+            block.clear();
+            for sy in y * factor..(y + 1) * factor {
+                let row = sy * source.width;
+                block.extend(source.buffer[row + x * factor..row + (x + 1) * factor].iter().map(|&c| Color::from_hex(c)));
+            }
+            target.buffer[y * target.width + x] = Color::average(&block).to_hex();
+        }
+    }
+}
+
+// Lanczos separable: primero reduce las filas y luego las columnas. Es más nítido
+// que el promedio, a cambio de un leve halo en los bordes muy contrastados
+pub fn downsample_lanczos(source: &Framebuffer, target: &mut Framebuffer, factor: usize) {
+    // Con un factor entero todos los píxeles de salida usan los mismos pesos
+    let radius = (LANCZOS_LOBES * factor as f32).ceil() as i64;
+    let center = factor as f32 / 2.0;
+    let taps: Vec<(i64, f32)> = (-radius..factor as i64 + radius)
+        .map(|i| (i, lanczos((i as f32 + 0.5 - center) / factor as f32)))
+        .filter(|&(_, weight)| weight != 0.0)
+        .collect();
+    let total: f32 = taps.iter().map(|&(_, weight)| weight).sum();
+
+    let filter = |read: &dyn Fn(i64) -> [f32; 3], start: i64| {
+        let mut sum = [0.0; 3];
+        for &(i, weight) in &taps {
+            let texel = read(start + i);
+            for channel in 0..3 {
+                sum[channel] += texel[channel] * weight;
+            }
+        }
+        sum.map(|value| value / total)
+    };
+
+    // Filas de la fuente reducidas en x
+    let mut rows = vec![[0.0; 3]; target.width * source.height];
+    for y in 0..source.height {
+        let line = &source.buffer[y * source.width..(y + 1) * source.width];
+        let read = |x: i64| rgb(line[x.clamp(0, source.width as i64 - 1) as usize]);
+        for x in 0..target.width {
+            rows[y * target.width + x] = filter(&read, (x * factor) as i64);
+        }
+    }
+
+    for x in 0..target.width {
+        let read = |y: i64| rows[y.clamp(0, source.height as i64 - 1) as usize * target.width + x];
+        for y in 0..target.height {
+            let [r, g, b] = filter(&read, (y * factor) as i64);
+            target.buffer[y * target.width + x] = Color::from_unit(r, g, b).to_hex();
+        }
+    }
+}
+
+fn lanczos(x: f32) -> f32 {
+    if x == 0.0 {
+        1.0
+    } else if x.abs() < LANCZOS_LOBES {
+        let px = PI * x;
+        LANCZOS_LOBES * px.sin() * (px / LANCZOS_LOBES).sin() / (px * px)
+    } else {
+        0.0
+    }
+}
+
+// Componentes lineales del píxel
+fn rgb(hex: u32) -> [f32; 3] {
+    let color = Color::from_hex(hex);
+    [color.r(), color.g(), color.b()]
+}
+
+// Luminancia perceptual: se calcula sobre los valores sRGB, sin decodificar
+fn luma(hex: u32) -> f32 {
+    let channel = |shift: u32| ((hex >> shift) & 0xFF) as f32 / 255.0;
+    0.299 * channel(16) + 0.587 * channel(8) + 0.114 * channel(0)
+}
+
+// FXAA (basado en la versión "quality" de Timothy Lottes): busca bordes por
+// contraste de luminancia, recorre el borde hasta sus extremos y mezcla cada
+// píxel con su vecino al otro lado del borde según lo cerca que esté del final
+pub fn fxaa(framebuffer: &mut Framebuffer) {
+    let (width, height) = (framebuffer.width as i64, framebuffer.height as i64);
+    let lumas: Vec<f32> = framebuffer.buffer.iter().map(|&c| luma(c)).collect();
+    let source = framebuffer.buffer.clone();
+
+    let at = |x: i64, y: i64| (y.clamp(0, height - 1) * width + x.clamp(0, width - 1)) as usize;
+    let luma_at = |x: i64, y: i64| lumas[at(x, y)];
+
+    for y in 0..height {
+        for x in 0..width {
+            let m = luma_at(x, y);
+            let (n, s, w, e) = (luma_at(x, y - 1), luma_at(x, y + 1), luma_at(x - 1, y), luma_at(x + 1, y));
+
+            let max = m.max(n).max(s).max(w).max(e);
+            let min = m.min(n).min(s).min(w).min(e);
+            let range = max - min;
+            if range < FXAA_EDGE_THRESHOLD_MIN.max(max * FXAA_EDGE_THRESHOLD) {
+                continue;
+            }
+
+            let (nw, ne) = (luma_at(x - 1, y - 1), luma_at(x + 1, y - 1));
+            let (sw, se) = (luma_at(x - 1, y + 1), luma_at(x + 1, y + 1));
+
+            // El borde es horizontal si la luminancia cambia más en vertical
+            let horizontal_change = (nw + sw - 2.0 * w).abs() + 2.0 * (n + s - 2.0 * m).abs() + (ne + se - 2.0 * e).abs();
+            let vertical_change = (nw + ne - 2.0 * n).abs() + 2.0 * (w + e - 2.0 * m).abs() + (sw + se - 2.0 * s).abs();
+            let horizontal = horizontal_change >= vertical_change;
+
+            // Lado del borde con el mayor gradiente: `across` apunta hacia él y
+            // `along` recorre el borde
+            let (luma1, luma2) = if horizontal { (n, s) } else { (w, e) };
+            let (gradient1, gradient2) = (luma1 - m, luma2 - m);
+            let first_steepest = gradient1.abs() >= gradient2.abs();
+            let gradient = gradient1.abs().max(gradient2.abs()) * 0.25;
+            let side_luma = if first_steepest { luma1 } else { luma2 };
+            let sign = if first_steepest { -1 } else { 1 };
+            let (across, along) = if horizontal { ((0, sign), (1, 0)) } else { ((sign, 0), (0, 1)) };
+
+            // Luminancia media justo sobre el borde, entre el píxel y su vecino
+            let edge_luma = |offset: i64| {
+                let (px, py) = (x + along.0 * offset, y + along.1 * offset);
+                (luma_at(px, py) + luma_at(px + across.0, py + across.1)) * 0.5
+            };
+            let local_average = (m + side_luma) * 0.5;
+
+            let explore = |direction: i64| {
+                let mut distance = 0;
+                let mut delta = 0.0;
+                for &step in &FXAA_STEPS {
+                    distance += step as i64;
+                    delta = edge_luma(direction * distance) - local_average;
+                    if delta.abs() >= gradient {
+                        break;
+                    }
+                }
+                (distance as f32, delta)
+            };
+            let (distance1, delta1) = explore(-1);
+            let (distance2, delta2) = explore(1);
+
+            // Solo se mezcla si el extremo más cercano cambia en el sentido contrario al píxel
+            let (distance, delta) = if distance1 < distance2 { (distance1, delta1) } else { (distance2, delta2) };
+            let center_smaller = m < local_average;
+            let edge_offset = if (delta < 0.0) != center_smaller {
+                0.5 - distance / (distance1 + distance2)
+            } else {
+                0.0
+            };
+
+            // Aliasing de subpíxel: el píxel difiere mucho del promedio de sus vecinos
+            let average = (2.0 * (n + s + w + e) + nw + ne + sw + se) / 12.0;
+            let subpixel = ((average - m).abs() / range).clamp(0.0, 1.0);
+            let subpixel = (-2.0 * subpixel + 3.0) * subpixel * subpixel;
+            let subpixel_offset = subpixel * subpixel * FXAA_SUBPIXEL_QUALITY;
+
+            let offset = edge_offset.max(subpixel_offset);
+            let neighbor = Color::from_hex(source[at(x + across.0, y + across.1)]);
+            let color = Color::from_hex(source[at(x, y)]).lerp(&neighbor, offset);
+            framebuffer.buffer[at(x, y)] = color.to_hex();
+        }
+    }
+}
@@ -0,0 +1,425 @@
+use nalgebra_glm::{Mat4, Vec3};
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+use crate::create_model_matrix;
+use crate::light::{LightDescription, PointLight};
+use crate::material::{Material, MaterialDescription, ShaderRegistry, SurfaceMaterial};
+use crate::noise::{NoiseDescription, NoiseRegistry};
+use crate::obj::{Mesh, Obj};
+use crate::orbit::OrbitalElements;
+use crate::pipeline::{BlendMode, CullMode, FrontFace, RasterState};
+use crate::texture::{FilterMode, Texture, WrapMode};
+
+// Formato del archivo de escena (RON), ver assets/scenes/solar_system.ron
+#[derive(Deserialize)]
+pub struct SceneDescription {
+    #[serde(default)]
+    pub noises: Vec<NoiseDescription>,
+    #[serde(default)]
+    pub textures: Vec<TextureDescription>,
+    pub bodies: Vec<BodyDescription>,
+}
+
+impl SceneDescription {
+    // Las rutas relativas de mallas y texturas pasan a ser relativas a `directory`
+    // (la carpeta del archivo de escena), como los mapas de un MTL respecto de su OBJ
+    pub fn resolve_paths(&mut self, directory: &Path) {
+        let resolve = |path: &mut String| *path = directory.join(&*path).to_string_lossy().into_owned();
+
+        for texture in &mut self.textures {
+            resolve(&mut texture.path);
+        }
+        for mesh in self.bodies.iter_mut().filter_map(|body| body.mesh.as_mut()) {
+            resolve(mesh);
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct BodyDescription {
+    pub name: String,
+    // Un cuerpo sin malla sirve como pivote para agrupar hijos
+    #[serde(default)]
+    pub mesh: Option<String>,
+    #[serde(default)]
+    pub material: Option<MaterialDescription>,
+    #[serde(default = "default_scale")]
+    pub scale: f32,
+    #[serde(default)]
+    pub parent: Option<String>,
+    // Posición y rotación relativas al padre; los hijos las heredan junto con la escala
+    #[serde(default)]
+    pub position: [f32; 3],
+    #[serde(default)]
+    pub rotation: [f32; 3],
+    // Giro propio en radianes por segundo; solo afecta a la malla del cuerpo, no a sus hijos
+    #[serde(default)]
+    pub spin: [f32; 3],
+    #[serde(default)]
+    pub orbit: Option<OrbitDescription>,
+    // El cuerpo emite luz desde su centro, como el sol
+    #[serde(default)]
+    pub light: Option<LightDescription>,
+    // Caras que se descartan y orden de los vértices de las caras frontales de la malla
+    #[serde(default)]
+    pub cull: CullMode,
+    #[serde(default)]
+    pub front_face: FrontFace,
+    // Mezcla con lo ya dibujado; los cuerpos que no son Replace se dibujan después de
+    // los opacos, de atrás hacia adelante, y no proyectan sombras
+    #[serde(default)]
+    pub blend: BlendMode,
+    #[serde(default = "default_depth_write")]
+    pub depth_write: bool,
+}
+
+// Elementos orbitales con los ángulos en grados
+#[derive(Deserialize)]
+pub struct OrbitDescription {
+    pub semi_major_axis: f32,
+    #[serde(default)]
+    pub eccentricity: f32,
+    #[serde(default)]
+    pub inclination: f32,
+    #[serde(default)]
+    pub longitude_of_ascending_node: f32,
+    #[serde(default)]
+    pub argument_of_periapsis: f32,
+    #[serde(default)]
+    pub mean_anomaly: f32,
+    pub period: f32,
+}
+
+impl From<OrbitDescription> for OrbitalElements {
+    fn from(orbit: OrbitDescription) -> Self {
+        OrbitalElements {
+            semi_major_axis: orbit.semi_major_axis,
+            eccentricity: orbit.eccentricity,
+            inclination: orbit.inclination.to_radians(),
+            longitude_of_ascending_node: orbit.longitude_of_ascending_node.to_radians(),
+            argument_of_periapsis: orbit.argument_of_periapsis.to_radians(),
+            mean_anomaly: orbit.mean_anomaly.to_radians(),
+            period: orbit.period,
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TextureDescription {
+    pub name: String,
+    pub path: String,
+    #[serde(default)]
+    pub filter: FilterMode,
+    #[serde(default)]
+    pub wrap_u: WrapMode,
+    #[serde(default)]
+    pub wrap_v: WrapMode,
+    #[serde(default = "default_anisotropy")]
+    pub max_anisotropy: u32,
+}
+
+fn default_anisotropy() -> u32 {
+    1
+}
+
+fn default_scale() -> f32 {
+    1.0
+}
+
+fn default_depth_write() -> bool {
+    true
+}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    Parse(ron::error::SpannedError),
+    Mesh(String, tobj::LoadError),
+    Texture(String, image::ImageError),
+    UnknownParent(String, String),
+    MissingMaterial(String),
+    UnknownShader(String, String),
+    InvalidMaterial(String, ron::Error),
+    InvalidOrbit(String),
+    UnknownNoise(String, String),
+    UnknownTexture(String, String),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(err) => write!(f, "no se pudo leer la escena: {}", err),
+            SceneError::Parse(err) => write!(f, "escena inválida: {}", err),
+            SceneError::Mesh(path, err) => write!(f, "no se pudo cargar {}: {}", path, err),
+            SceneError::Texture(path, err) => write!(f, "no se pudo cargar {}: {}", path, err),
+            SceneError::UnknownParent(body, parent) => {
+                write!(f, "el padre '{}' de '{}' no está declarado antes en la escena", parent, body)
+            }
+            SceneError::MissingMaterial(body) => write!(f, "'{}' tiene malla pero no material", body),
+            SceneError::UnknownShader(body, shader) => write!(f, "el shader '{}' de '{}' no existe", shader, body),
+            SceneError::InvalidMaterial(body, err) => write!(f, "parámetros inválidos en el material de '{}': {}", body, err),
+            SceneError::InvalidOrbit(body) => {
+                write!(f, "la órbita de '{}' necesita 0 <= eccentricity < 1 y period distinto de 0", body)
+            }
+            SceneError::UnknownNoise(body, noise) => {
+                write!(f, "el ruido '{}' de '{}' no está declarado en la escena", noise, body)
+            }
+            SceneError::UnknownTexture(body, texture) => {
+                write!(f, "la textura '{}' de '{}' no está declarada en la escena", texture, body)
+            }
+        }
+    }
+}
+
+impl std::error::Error for SceneError {}
+
+fn load_model(
+    obj: Obj,
+    textures: &mut Vec<Texture>,
+    map_ids: &mut HashMap<(String, bool), usize>,
+) -> Result<Model, SceneError> {
+    let (meshes, materials) = obj.into_parts();
+
+    // `linear`: la imagen guarda datos (normales) en lugar de colores sRGB
+    let mut load_map = |path: &Option<String>, linear: bool| -> Result<Option<usize>, SceneError> {
+        let Some(path) = path else {
+            return Ok(None);
+        };
+        if let Some(&id) = map_ids.get(&(path.clone(), linear)) {
+            return Ok(Some(id));
+        }
+
+        let loaded = if linear { Texture::load_linear(path) } else { Texture::load(path) };
+        let mut texture = loaded.map_err(|err| SceneError::Texture(path.clone(), err))?;
+        texture.filter = FilterMode::Trilinear;
+        textures.push(texture);
+        map_ids.insert((path.clone(), linear), textures.len() - 1);
+        Ok(Some(textures.len() - 1))
+    };
+
+    let mut surfaces = Vec::with_capacity(materials.len());
+    for material in &materials {
+        let diffuse_map = load_map(&material.diffuse_map, false)?;
+        let normal_map = load_map(&material.normal_map, true)?;
+        surfaces.push(SurfaceMaterial::new(material, diffuse_map, normal_map));
+    }
+
+    Ok(Model { meshes, surfaces })
+}
+
+fn load_material(
+    body: &str,
+    material: MaterialDescription,
+    shaders: &ShaderRegistry,
+    noises: &NoiseRegistry,
+    texture_ids: &HashMap<String, usize>,
+) -> Result<Material, SceneError> {
+    let shader = shaders
+        .create(&material.shader, material.params)
+        .ok_or_else(|| SceneError::UnknownShader(body.to_string(), material.shader.clone()))?
+        .map_err(|err| SceneError::InvalidMaterial(body.to_string(), err))?;
+
+    let noise = match &material.noise {
+        Some(name) => noises
+            .handle(name)
+            .ok_or_else(|| SceneError::UnknownNoise(body.to_string(), name.clone()))?,
+        None => noises.default_handle(),
+    };
+
+    let texture = match &material.texture {
+        Some(name) => Some(
+            *texture_ids
+                .get(name)
+                .ok_or_else(|| SceneError::UnknownTexture(body.to_string(), name.clone()))?,
+        ),
+        None => None,
+    };
+
+    Ok(Material { shader, noise, texture })
+}
+
+pub struct Body {
+    pub name: String,
+    pub mesh: Option<usize>,
+    pub material: Option<Material>,
+    pub scale: f32,
+    pub parent: Option<usize>,
+    pub position: Vec3,
+    pub rotation: Vec3,
+    pub spin: Vec3,
+    pub spin_angle: Vec3,
+    pub orbit: Option<OrbitalElements>,
+    pub light: Option<LightDescription>,
+    pub raster_state: RasterState,
+    // Transformación local compuesta con la de todos los padres
+    pub world_matrix: Mat4,
+}
+
+impl Body {
+    pub fn model_matrix(&self) -> Mat4 {
+        self.world_matrix * create_model_matrix(Vec3::new(0.0, 0.0, 0.0), 1.0, self.spin_angle)
+    }
+
+    pub fn world_position(&self) -> Vec3 {
+        Vec3::new(self.world_matrix[(0, 3)], self.world_matrix[(1, 3)], self.world_matrix[(2, 3)])
+    }
+}
+
+// Submallas de un archivo OBJ con los materiales de su MTL
+pub struct Model {
+    pub meshes: Vec<Mesh>,
+    pub surfaces: Vec<SurfaceMaterial>,
+}
+
+pub struct Scene {
+    pub bodies: Vec<Body>,
+    pub models: Vec<Model>,
+    pub noises: NoiseRegistry,
+    pub textures: Vec<Texture>,
+}
+
+impl Scene {
+    pub fn load(path: &Path) -> Result<Self, SceneError> {
+        let source = fs::read_to_string(path).map_err(SceneError::Io)?;
+        let mut description: SceneDescription = ron::from_str(&source).map_err(SceneError::Parse)?;
+        description.resolve_paths(path.parent().unwrap_or(Path::new("")));
+        Scene::from_description(description, &ShaderRegistry::new())
+    }
+
+    // `shaders` resuelve los nombres de shader de los materiales
+    pub fn from_description(description: SceneDescription, shaders: &ShaderRegistry) -> Result<Self, SceneError> {
+        let mut models = Vec::new();
+        let mut mesh_ids: HashMap<String, usize> = HashMap::new();
+        let mut body_ids: HashMap<String, usize> = HashMap::new();
+        let mut bodies = Vec::with_capacity(description.bodies.len());
+
+        let mut noises = NoiseRegistry::new();
+        for noise in &description.noises {
+            noises.insert(&noise.name, noise.build());
+        }
+
+        let mut textures = Vec::with_capacity(description.textures.len());
+        let mut texture_ids: HashMap<String, usize> = HashMap::new();
+        for texture in &description.textures {
+            let mut loaded = Texture::load(&texture.path)
+                .map_err(|err| SceneError::Texture(texture.path.clone(), err))?;
+            loaded.filter = texture.filter;
+            loaded.wrap_u = texture.wrap_u;
+            loaded.wrap_v = texture.wrap_v;
+            loaded.max_anisotropy = texture.max_anisotropy;
+            texture_ids.insert(texture.name.clone(), textures.len());
+            textures.push(loaded);
+        }
+
+        // Mapas de los archivos MTL, cargados una vez por ruta
+        let mut map_ids: HashMap<(String, bool), usize> = HashMap::new();
+
+        for body in description.bodies {
+            if body.mesh.is_some() && body.material.is_none() {
+                return Err(SceneError::MissingMaterial(body.name));
+            }
+            if let Some(orbit) = &body.orbit {
+                if !(0.0..1.0).contains(&orbit.eccentricity) || orbit.period == 0.0 {
+                    return Err(SceneError::InvalidOrbit(body.name));
+                }
+            }
+
+            // Cada malla se carga una sola vez aunque la usen varios cuerpos
+            let mesh = match &body.mesh {
+                Some(path) => Some(match mesh_ids.get(path) {
+                    Some(&id) => id,
+                    None => {
+                        let obj = Obj::load(path).map_err(|err| SceneError::Mesh(path.clone(), err))?;
+                        models.push(load_model(obj, &mut textures, &mut map_ids)?);
+                        mesh_ids.insert(path.clone(), models.len() - 1);
+                        models.len() - 1
+                    }
+                }),
+                None => None,
+            };
+
+            // Los padres deben declararse antes que sus hijos
+            let parent = match &body.parent {
+                Some(name) => Some(
+                    *body_ids
+                        .get(name)
+                        .ok_or_else(|| SceneError::UnknownParent(body.name.clone(), name.clone()))?,
+                ),
+                None => None,
+            };
+
+            let material = match body.material {
+                Some(material) => Some(load_material(&body.name, material, shaders, &noises, &texture_ids)?),
+                None => None,
+            };
+
+            body_ids.insert(body.name.clone(), bodies.len());
+            bodies.push(Body {
+                name: body.name,
+                mesh,
+                material,
+                scale: body.scale,
+                parent,
+                position: Vec3::from(body.position),
+                rotation: Vec3::from(body.rotation),
+                spin: Vec3::from(body.spin),
+                spin_angle: Vec3::new(0.0, 0.0, 0.0),
+                orbit: body.orbit.map(OrbitalElements::from),
+                light: body.light,
+                raster_state: RasterState {
+                    blend: body.blend,
+                    depth_write: body.depth_write,
+                    ..RasterState::new(body.cull, body.front_face)
+                },
+                world_matrix: Mat4::identity(),
+            });
+        }
+
+        let mut scene = Scene { bodies, models, noises, textures };
+        scene.update_transforms();
+        Ok(scene)
+    }
+
+    // Luz del primer cuerpo que emite, en su posición actual
+    pub fn light(&self) -> Option<PointLight> {
+        self.bodies.iter().find_map(|body| {
+            body.light
+                .as_ref()
+                .map(|light| PointLight::new(body.world_position(), light.color, light.intensity, light.ambient))
+        })
+    }
+
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.bodies.iter().position(|body| body.name == name)
+    }
+
+    // Coloca la escena en el instante `time` (segundos, puede ser negativo).
+    // Las órbitas se calculan en el espacio del padre.
+    pub fn update(&mut self, time: f32) {
+        for body in &mut self.bodies {
+            if let Some(orbit) = &body.orbit {
+                body.position = orbit.position_at(time);
+            }
+            body.spin_angle = body.spin * time;
+        }
+
+        self.update_transforms();
+    }
+
+    // Como los padres van antes que los hijos, una sola pasada basta
+    pub fn update_transforms(&mut self) {
+        for i in 0..self.bodies.len() {
+            let parent_matrix = match self.bodies[i].parent {
+                Some(parent) => self.bodies[parent].world_matrix,
+                None => Mat4::identity(),
+            };
+
+            let body = &mut self.bodies[i];
+            body.world_matrix = parent_matrix * create_model_matrix(body.position, body.scale, body.rotation);
+        }
+    }
+}
@@ -1,12 +1,14 @@
-use nalgebra_glm::{Vec3, Vec4, Mat3, mat4_to_mat3};
+use nalgebra_glm::{Vec2, Vec3, Vec4, Mat3, mat4_to_mat3, cross, dot};
 use crate::vertex::Vertex;
 use crate::Uniforms;
 use crate::fragment::Fragment;
 use crate::color::Color;
-use rand::Rng; // Mantener solo si se usa
-use rand::SeedableRng; // Mantener solo si se usa
-use rand::rngs::StdRng; // Mantener solo si se usa
-use crate::CelestialBody;
+use crate::texture::{Texture, spherical_uv};
+use rand::Rng;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use serde::Deserialize;
+use std::f32::consts::PI;
 
 pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
   let position = Vec4::new(
@@ -16,17 +18,10 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
       1.0
   );
 
-  let transformed = uniforms.projection_matrix * uniforms.view_matrix * uniforms.model_matrix * position;
+  let world_position = uniforms.model_matrix * position;
 
-  let w = transformed.w;
-  let transformed_position = Vec4::new(
-      transformed.x / w,
-      transformed.y / w,
-      transformed.z / w,
-      1.0
-  );
-
-  let screen_position = uniforms.viewport_matrix * transformed_position; // Asegúrate de que esta línea esté presente
+  // Se queda en espacio de recorte: la división por w ocurre después del recorte
+  let clip_position = uniforms.projection_matrix * uniforms.view_matrix * world_position;
 
   let model_mat3 = mat4_to_mat3(&uniforms.model_matrix);
   let normal_matrix = model_mat3.transpose().try_inverse().unwrap_or(Mat3::identity());
@@ -39,8 +34,10 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
       normal: vertex.normal,
       tex_coords: vertex.tex_coords,
       color: vertex.color, // Color inicial
-      transformed_position: Vec3::new(screen_position.x, screen_position.y, screen_position.z),
-      transformed_normal: transformed_normal,
+      transformed_position: Vec3::new(0.0, 0.0, 0.0),
+      transformed_normal,
+      world_position: world_position.xyz(),
+      clip_position,
       elevation: vertex.elevation, // Mantiene la elevación original
   };
 
@@ -50,237 +47,619 @@ pub fn vertex_shader(vertex: &Vertex, uniforms: &Uniforms) -> Vertex {
   new_vertex
 }
 
+// Sombreado por fragmento de un material. Cada cuerpo guarda su propio shader con
+// sus parámetros; para un aspecto nuevo basta con implementar este trait y
+// registrarlo en `ShaderRegistry`.
+pub trait Shader: Send + Sync {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color;
+}
+
 pub fn fragment_shader(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    match uniforms.current_body {
-       CelestialBody::earth => earth(fragment, uniforms),
-       CelestialBody::Moon => moon_color(fragment, uniforms),
-       CelestialBody::sun => sun_gradient(fragment, uniforms),
-       CelestialBody::gas => gas_planet_color(fragment, uniforms),
-       CelestialBody::rocky => rocky_planet_color(fragment, uniforms),
-       CelestialBody::nave => spaceship_color(fragment, uniforms),
-       CelestialBody::star => star_planet_color(fragment, uniforms),
-     
-
-    }
-  }
-
-  fn earth(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let position = fragment.vertex_position;
-    let time = uniforms.time as f32 * 0.01;
-
-    let surface_color = Color::new(255, 150, 50);
-    let land_color = Color::new(255, 100, 0);
-    let cloud_color = Color::new(255, 255, 255);
-
-    let surface = uniforms.noise.get_noise_2d(position.x * 50.0, position.y * 50.0);
-    let clouds = uniforms.noise.get_noise_3d(position.x * 50.0 + time, position.y * 50.0 + time * 0.5, time);
-
-    let base_color = if surface > 0.1 {
-        land_color
-    } else {
-        surface_color
-    };
-
-    let final_color = if clouds > 0.4 {
-        base_color.lerp(&cloud_color, (clouds - 0.4) * 2.0)
-    } else {
-        base_color
-    };
-
-    final_color // Eliminado ajuste por intensidad de luz
-}
-
-fn moon_color(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let noise_value = uniforms.noise.get_noise_2d(fragment.vertex_position.x * 20.0, fragment.vertex_position.z * 20.0);
-    let elevation = noise_value;
-
-    let low_threshold = -0.1;
-    let medium_threshold = 0.1;
-    let high_threshold = 0.3;
-
-    let dark_surface_color = Color::new(169, 169, 169);
-    let light_surface_color = Color::new(211, 211, 211);
-    let crater_color = Color::new(255, 255, 255);
-
-    let color = if elevation < low_threshold {
-        dark_surface_color
-    } else if elevation < medium_threshold {
-        light_surface_color
-    } else if elevation < high_threshold {
-        crater_color
-    } else {
-        Color::new(240, 240, 240)
-    };
-
-    color // Eliminado ajuste por intensidad de luz
-}
-
-
-fn sun_gradient(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    // Obtiene un valor de ruido para efectos adicionales (opcional).
-    let noise_value = uniforms.noise.get_noise_2d(fragment.vertex_position.x * 10.0, fragment.vertex_position.z * 10.0);
-    
-    // Define colores representativos para el sol en tonos naranjas.
-    let deep_orange_color = Color::new(255, 140, 0); // Naranja profundo.
-    let light_orange_color = Color::new(255, 165, 80); // Naranja claro.
-    let white_color = Color::new(255, 255, 255); // Blanco para el brillo.
-    let warm_orange_color = Color::new(255, 200, 100); // Naranja cálido para el resplandor.
-
-    // Determina la posición relativa del fragmento para el difuminado.
-    let distance_to_sun = (fragment.vertex_position.y - 5.0).abs(); // Ajusta la altura según necesites.
-    
-    // Calcula un factor de difuminado basado en la distancia.
-    let gradient_factor = (1.0 - distance_to_sun / 10.0).max(0.0).min(1.0);
-    
-    // Calcula el color difuminado combinando los colores.
-    let sun_color = 
-        deep_orange_color * gradient_factor * 0.5 + 
-        light_orange_color * (1.0 - gradient_factor) * 0.5 + 
-        warm_orange_color * gradient_factor * 0.3; // Añadiendo naranja cálido para mayor luminosidad.
-
-    // Agrega un brillo adicional alrededor del sol.
-    let glow_color = white_color * 0.3 * gradient_factor; // Brillo suave alrededor del sol.
-    
-    // Combina el color del sol y el brillo.
-    let final_color = sun_color + glow_color;
-
-    // Crea variaciones adicionales para simular partes del sol y su halo.
-    if noise_value > 0.2 {
-        let halo_color = Color::new(255, 160, 50); // Color del halo en un tono naranja más suave.
-        let halo_factor = (noise_value - 0.2).min(0.5); // Intensifica el halo basado en el ruido.
-        return final_color + halo_color * halo_factor; // Combina el color del halo.
-    }
-
-    final_color
-}
-
-
-fn gas_planet_color(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    // Utiliza la posición del fragmento y el tiempo para generar un "seed" para el ruido.
-    let seed = uniforms.time as f32 * fragment.vertex_position.y * fragment.vertex_position.x;
-    
-    // Crea un generador de números aleatorios basado en el seed.
-    let mut rng = StdRng::seed_from_u64(seed.abs() as u64);
-    
-    // Genera un número aleatorio para la variación en el color.
-    let random_number = rng.gen_range(0..=100);
-
-    // Define colores base para el planeta gaseoso.
-    let base_color = Color::new(70, 130, 180); // Azul
-    let cloud_color = Color::new(255, 255, 255); // Blanco para nubes
-    let shadow_color = Color::new(50, 50, 100); // Color oscuro para sombras
-
-    // Calcular el factor de nubes usando el ruido
-    let noise_value = uniforms.noise.get_noise_2d(fragment.vertex_position.x * 5.0, fragment.vertex_position.z * 5.0);
-    let cloud_factor = (noise_value * 0.5 + 0.5).powi(2); // Escala el ruido entre 0 y 1.
-
-    // Selección de color basado en el número aleatorio para agregar variación.
-    let planet_color = if random_number < 50 {
-        base_color * (1.0 - cloud_factor) + cloud_color * cloud_factor
-    } else {
-        cloud_color * cloud_factor // Predominan las nubes
-    };
-
-    // Añadir sombras sutiles
-    let shadow_factor = (1.0 - noise_value).max(0.0);
-    let shadow_effect = shadow_color * shadow_factor * 0.3; // Sombra suave
-
-    // Combina el color del planeta y las sombras
-    let final_color = planet_color + shadow_effect;
-
-    // Brillo atmosférico (opcional)
-    let glow_color = Color::new(200, 200, 255); // Brillo azul claro
-    let glow_factor = (1.0 - (fragment.vertex_position.y / 10.0).max(0.0).min(1.0)).max(0.0); // Basado en altura
-    let final_glow = glow_color * glow_factor * 0.1; // Brillo sutil
-
-    // Devuelve el color final combinado
-    final_color + final_glow
-}
-
-
-fn rocky_planet_color(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-   // Define colores base para el planeta fantástico.
-   let base_color = Color::new(50, 50, 200); // Azul profundo.
-   let accent_color = Color::new(180, 80, 200); // Púrpura vibrante para los detalles.
-   let cloud_color = Color::new(255, 255, 255); // Blanco para las nubes.
-
-   // Cálculo del ruido para la textura del planeta
-   let noise_value = uniforms.noise.get_noise_2d(fragment.vertex_position.x * 2.0, fragment.vertex_position.z * 2.0);
-   let cloud_factor = (noise_value * 0.5 + 0.5).powi(3); // Escala el ruido para suavizar la textura.
-
-   // Calcula un desplazamiento basado en la posición Y del fragmento para crear capas.
-   let layer_offset = (fragment.vertex_position.y * 0.5).sin() * 0.5; // Controla la "altura" de las capas.
-   
-   // Determina el color del fragmento en función de su altura
-   let planet_color = if fragment.vertex_position.y > 0.0 {
-       // En la parte superior del planeta, añade un efecto de nubes
-       base_color * (1.0 - cloud_factor) + cloud_color * cloud_factor
-   } else {
-       // En la parte inferior del planeta, utiliza el color de acento
-       accent_color * (1.0 + layer_offset)
-   };
-
-   // Efecto de brillo para dar profundidad
-   let brightness = 0.1; // Brillo constante
-   let light_effect = Color::new((brightness * 255.0) as u8, (brightness * 255.0) as u8, (brightness * 255.0) as u8);
-
-   // Devuelve el color final combinado
-   planet_color + light_effect
-}
-
-
-
-fn spaceship_color(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    let position = fragment.vertex_position;
-    let time = uniforms.time as f32 * 0.01;
-    
-    // Definir el color base de la nave (metálica, en tonos plateados o oscuros)
-    let base_color = Color::new(180, 180, 180); // Gris metálico
-    let highlight_color = Color::new(255, 255, 255); // Blanco para resplandores
-    
-    // Efecto de iluminación: Simulamos una fuente de luz (por ejemplo, el sol o una estrella cercana)
-    let light_direction = Vec3::new(1.0, 1.0, -1.0).normalize(); // Dirección de la luz
-        // Brillo atmosférico (opcional)
-    let glow_color = Color::new(255, 140, 0); // Brillo anaranjado
-    let glow_factor = (1.0 - (fragment.vertex_position.y / 10.0).max(0.0).min(1.0)).max(0.0); // Basado en altura
-    let final_glow = glow_color * glow_factor * 0.1; // Brillo sutil
-
-    // Simulación de resplandor (halo o brillo alrededor de la nave)
-    // Devuelve el color final combinado
-    base_color + final_glow
+    uniforms.shader.shade(fragment, uniforms)
+}
+
+// Color de la textura del material, multiplicado por `tint`
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct TextureShader {
+    // Usa longitud y latitud de la posición en el modelo en vez de sus UVs
+    // (p. ej. sphere.obj, cuyas UVs van por cara)
+    pub spherical: bool,
+    pub tint: Color,
+    // Se usa si el material no tiene textura
+    pub fallback_color: Color,
+    // Emite su propio color sin iluminar, p. ej. para un sol con textura
+    pub emissive: bool,
+    #[serde(flatten)]
+    pub lighting: Lighting,
+}
+
+impl Default for TextureShader {
+    fn default() -> Self {
+        TextureShader {
+            spherical: false,
+            tint: Color::new(255, 255, 255),
+            fallback_color: Color::new(255, 0, 255),
+            emissive: false,
+            lighting: Lighting::default(),
+        }
+    }
+}
+
+impl TextureShader {
+    fn albedo(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let Some(texture) = uniforms.texture else {
+            return self.fallback_color;
+        };
+
+        let (uv, uv_dx, uv_dy) = if self.spherical {
+            let position = fragment.vertex_position;
+            let uv = spherical_uv(position);
+            (
+                uv,
+                wrap_longitude(spherical_uv(position + fragment.vertex_position_dx) - uv),
+                wrap_longitude(spherical_uv(position + fragment.vertex_position_dy) - uv),
+            )
+        } else {
+            (fragment.tex_coords, fragment.tex_coords_dx, fragment.tex_coords_dy)
+        };
+
+        texture.sample_grad(uv, uv_dx, uv_dy).blend_multiply(&self.tint)
+    }
+}
+
+impl Shader for TextureShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let albedo = self.albedo(fragment, uniforms);
+        if self.emissive {
+            return albedo;
+        }
+        self.lighting.apply(albedo, fragment, uniforms)
+    }
+}
+
+// Colores y mapas del material MTL de cada submalla (Ka, Kd, Ks, Ns, map_Kd, map_Bump)
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct MtlShader {
+    // Para submallas sin material
+    pub fallback_color: Color,
+}
+
+impl Default for MtlShader {
+    fn default() -> Self {
+        MtlShader {
+            fallback_color: Color::new(204, 204, 204),
+        }
+    }
+}
+
+impl Shader for MtlShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let Some(surface) = uniforms.surface else {
+            return Lighting::DIFFUSE.apply(self.fallback_color, fragment, uniforms);
+        };
+
+        let normal = match uniforms.normal_map {
+            Some(map) => perturb_normal(fragment, uniforms, map),
+            None => fragment.normal,
+        };
+
+        let light = &uniforms.light;
+        let (intensity, highlight) =
+            light.blinn_phong(fragment.world_position, normal, uniforms.camera_position, surface.shininess);
+        let visibility = shadow_visibility(fragment, uniforms);
+        let (intensity, highlight) = (intensity * visibility, highlight * visibility);
+
+        // La opacidad combina el alfa de map_Kd con la `d` del material
+        let diffuse = match uniforms.texture {
+            Some(texture) => {
+                let texel = texture.sample_grad(fragment.tex_coords, fragment.tex_coords_dx, fragment.tex_coords_dy);
+                texel.blend_multiply(&surface.diffuse).with_alpha(texel.a() * surface.diffuse.a())
+            }
+            None => surface.diffuse,
+        };
+        let opacity = diffuse.a();
+
+        let diffuse = diffuse.blend_multiply(&light.color);
+        let ambient = surface.ambient.blend_multiply(&diffuse) * light.ambient;
+        let specular = surface.specular.blend_multiply(&light.color) * highlight;
+
+        (ambient + diffuse * intensity + specular).with_alpha(opacity)
+    }
+}
+
+// Parámetros de Blinn-Phong que comparten los shaders iluminados; van junto a los
+// demás `params` del material, p. ej. (shader: "earth", params: (specular: 0.3, shininess: 32.0))
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(default)]
+pub struct Lighting {
+    // Escala del reflejo, que toma el color de la luz (0 lo desactiva)
+    pub specular: f32,
+    // Exponente de Blinn-Phong: cuanto mayor, más pequeño el reflejo
+    pub shininess: f32,
+}
+
+impl Default for Lighting {
+    fn default() -> Self {
+        Lighting { specular: 0.1, shininess: 16.0 }
+    }
+}
+
+impl Lighting {
+    // Solo luz ambiente y difusa, sin reflejo
+    pub const DIFFUSE: Lighting = Lighting { specular: 0.0, shininess: 1.0 };
+
+    // Ilumina `albedo` con la luz puntual de la escena
+    pub fn apply(&self, albedo: Color, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let light = &uniforms.light;
+        let (diffuse, highlight) =
+            light.blinn_phong(fragment.world_position, fragment.normal, uniforms.camera_position, self.shininess);
+        let visibility = shadow_visibility(fragment, uniforms);
+        let (diffuse, highlight) = (diffuse * visibility, highlight * visibility);
+
+        albedo.blend_multiply(&light.color) * (light.ambient + diffuse) + light.color * (self.specular * highlight)
+    }
+}
+
+// La luz ambiente no se ve afectada por las sombras
+fn shadow_visibility(fragment: &Fragment, uniforms: &Uniforms) -> f32 {
+    uniforms
+        .shadow
+        .map_or(1.0, |shadow| shadow.visibility(fragment.world_position, fragment.normal))
 }
 
+// Aplica el mapa de normales sin tangentes precalculadas: la base tangente sale de
+// las derivadas en pantalla de la posición y de las UVs
+fn perturb_normal(fragment: &Fragment, uniforms: &Uniforms, map: &Texture) -> Vec3 {
+    let normal = fragment.normal;
+    let (dp_dx, dp_dy) = (fragment.vertex_position_dx, fragment.vertex_position_dy);
+    let (duv_dx, duv_dy) = (fragment.tex_coords_dx, fragment.tex_coords_dy);
+
+    let det = duv_dx.x * duv_dy.y - duv_dy.x * duv_dx.y;
+    if det.abs() < 1e-12 {
+        return normal;
+    }
+
+    // Tangente y bitangente en espacio del objeto, llevadas al del mundo como la normal
+    let model = mat4_to_mat3(&uniforms.model_matrix);
+    let tangent = model * ((dp_dx * duv_dy.y - dp_dy * duv_dx.y) / det);
+    let bitangent = model * ((dp_dy * duv_dx.x - dp_dx * duv_dy.x) / det);
+
+    let tangent = tangent - normal * dot(&normal, &tangent);
+    if tangent.norm() < 1e-12 {
+        return normal;
+    }
+    let tangent = tangent.normalize();
+    let mut bitangent_ortho = cross(&normal, &tangent);
+    if dot(&bitangent_ortho, &bitangent) < 0.0 {
+        bitangent_ortho = -bitangent_ortho;
+    }
+
+    let sample = map.sample_grad(fragment.tex_coords, duv_dx, duv_dy);
+    let unpack = |c: f32| c * 2.0 - 1.0;
+    let (x, y, z) = (unpack(sample.r()), unpack(sample.g()), unpack(sample.b()));
+
+    (tangent * x + bitangent_ortho * y + normal * z).normalize()
+}
+
+// Cruzar el meridiano de la costura no debe parecer un salto de toda la textura
+fn wrap_longitude(delta: Vec2) -> Vec2 {
+    Vec2::new(delta.x - delta.x.round(), delta.y)
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct EarthShader {
+    pub surface_color: Color,
+    pub land_color: Color,
+    pub cloud_color: Color,
+    pub noise_scale: f32,
+    pub land_threshold: f32,
+    pub cloud_threshold: f32,
+    // Velocidad con la que se mueven las nubes
+    pub cloud_speed: f32,
+    #[serde(flatten)]
+    pub lighting: Lighting,
+}
+
+impl Default for EarthShader {
+    fn default() -> Self {
+        EarthShader {
+            surface_color: Color::new(255, 150, 50),
+            land_color: Color::new(255, 100, 0),
+            cloud_color: Color::new(255, 255, 255),
+            noise_scale: 50.0,
+            land_threshold: 0.1,
+            cloud_threshold: 0.4,
+            cloud_speed: 0.6,
+            lighting: Lighting::default(),
+        }
+    }
+}
+
+impl EarthShader {
+    fn albedo(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let position = fragment.vertex_position;
+        let time = uniforms.time * self.cloud_speed;
+        let scale = self.noise_scale;
+
+        let surface = uniforms.noise.get_noise_2d(position.x * scale, position.y * scale);
+        let clouds = uniforms.noise.get_noise_3d(position.x * scale + time, position.y * scale + time * 0.5, time);
+
+        let base_color = if surface > self.land_threshold {
+            self.land_color
+        } else {
+            self.surface_color
+        };
+
+        if clouds > self.cloud_threshold {
+            base_color.lerp(&self.cloud_color, (clouds - self.cloud_threshold) * 2.0)
+        } else {
+            base_color
+        }
+    }
+}
+
+impl Shader for EarthShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        self.lighting.apply(self.albedo(fragment, uniforms), fragment, uniforms)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct MoonShader {
+    pub dark_surface_color: Color,
+    pub light_surface_color: Color,
+    pub crater_color: Color,
+    pub peak_color: Color,
+    pub noise_scale: f32,
+    pub low_threshold: f32,
+    pub medium_threshold: f32,
+    pub high_threshold: f32,
+    #[serde(flatten)]
+    pub lighting: Lighting,
+}
+
+impl Default for MoonShader {
+    fn default() -> Self {
+        MoonShader {
+            dark_surface_color: Color::new(169, 169, 169),
+            light_surface_color: Color::new(211, 211, 211),
+            crater_color: Color::new(255, 255, 255),
+            peak_color: Color::new(240, 240, 240),
+            noise_scale: 20.0,
+            low_threshold: -0.1,
+            medium_threshold: 0.1,
+            high_threshold: 0.3,
+            lighting: Lighting::default(),
+        }
+    }
+}
+
+impl MoonShader {
+    fn albedo(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let elevation = uniforms.noise.get_noise_2d(
+            fragment.vertex_position.x * self.noise_scale,
+            fragment.vertex_position.z * self.noise_scale,
+        );
+
+        if elevation < self.low_threshold {
+            self.dark_surface_color
+        } else if elevation < self.medium_threshold {
+            self.light_surface_color
+        } else if elevation < self.high_threshold {
+            self.crater_color
+        } else {
+            self.peak_color
+        }
+    }
+}
+
+impl Shader for MoonShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        self.lighting.apply(self.albedo(fragment, uniforms), fragment, uniforms)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct SunShader {
+    pub deep_color: Color,
+    pub light_color: Color,
+    pub warm_color: Color,
+    pub glow_color: Color,
+    pub halo_color: Color,
+    pub noise_scale: f32,
+    // A partir de este valor de ruido aparece el halo
+    pub halo_threshold: f32,
+    // Multiplica la emisión; por encima de 1 el sol pasa a HDR y alimenta el bloom
+    pub intensity: f32,
+}
 
-fn star_planet_color(fragment: &Fragment, uniforms: &Uniforms) -> Color {
-    // Utiliza la posición del fragmento y el tiempo para generar un "seed" para el ruido.
-    let seed = uniforms.time as f32 * fragment.vertex_position.y * fragment.vertex_position.x;
-
-    // Crea un generador de números aleatorios basado en el seed.
-    let mut rng = StdRng::seed_from_u64(seed.abs() as u64);
-    
-    // Define colores base para el planeta estrellado.
-    let base_color = Color::new(30, 30, 60); // Azul oscuro para el fondo del planeta
-    let star_color = Color::new(255, 255, 255); // Blanco para las estrellas
-
-    // Calcular el factor de ruido para las estrellas
-    let noise_value = uniforms.noise.get_noise_2d(fragment.vertex_position.x * 5.0, fragment.vertex_position.z * 5.0);
-    let star_factor = (noise_value * 0.5 + 0.5).powi(2); // Escala el ruido entre 0 y 1.
-
-    // Determinar si hay una estrella en este fragmento
-    let random_number = rng.gen_range(0..=100);
-    let is_star = random_number < (star_factor * 100.0) as u32; // Convertir el star_factor a un rango de 0 a 100.
-
-    // Calcular el color del fragmento
-    let planet_color = if is_star {
-        star_color // Si es una estrella, usa el color de la estrella
-    } else {
-        base_color // Si no, usa el color de fondo
-    };
-
-    // Añadir un brillo para las estrellas
-    let brightness = if is_star { 0.7 } else { 0.0 }; // Brillo solo si es una estrella
-    let star_effect = star_color * brightness;
-
-    // Devuelve el color final combinado
-    planet_color + star_effect
+impl Default for SunShader {
+    fn default() -> Self {
+        SunShader {
+            deep_color: Color::new(255, 140, 0), // Naranja profundo.
+            light_color: Color::new(255, 165, 80), // Naranja claro.
+            warm_color: Color::new(255, 200, 100), // Naranja cálido para el resplandor.
+            glow_color: Color::new(255, 255, 255), // Blanco para el brillo.
+            halo_color: Color::new(255, 160, 50), // Color del halo en un tono naranja más suave.
+            noise_scale: 10.0,
+            halo_threshold: 0.2,
+            intensity: 1.0,
+        }
+    }
+}
+
+impl Shader for SunShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let noise_value = uniforms.noise.get_noise_2d(
+            fragment.vertex_position.x * self.noise_scale,
+            fragment.vertex_position.z * self.noise_scale,
+        );
+
+        // Determina la posición relativa del fragmento para el difuminado.
+        let distance_to_sun = (fragment.vertex_position.y - 5.0).abs();
+        let gradient_factor = (1.0 - distance_to_sun / 10.0).clamp(0.0, 1.0);
+
+        let sun_color =
+            self.deep_color * gradient_factor * 0.5 +
+            self.light_color * (1.0 - gradient_factor) * 0.5 +
+            self.warm_color * gradient_factor * 0.3;
+
+        // Brillo suave alrededor del sol.
+        let final_color = sun_color + self.glow_color * 0.3 * gradient_factor;
+
+        // Variaciones adicionales para simular partes del sol y su halo.
+        if noise_value > self.halo_threshold {
+            let halo_factor = (noise_value - self.halo_threshold).min(0.5);
+            return (final_color + self.halo_color * halo_factor) * self.intensity;
+        }
+
+        final_color * self.intensity
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct GasShader {
+    pub base_color: Color,
+    pub cloud_color: Color,
+    pub shadow_color: Color,
+    pub glow_color: Color,
+    pub noise_scale: f32,
+    // Franjas horizontales de nubes; 0 las desactiva
+    pub bands: f32,
+    #[serde(flatten)]
+    pub lighting: Lighting,
+}
+
+impl Default for GasShader {
+    fn default() -> Self {
+        GasShader {
+            base_color: Color::new(70, 130, 180), // Azul
+            cloud_color: Color::new(255, 255, 255), // Blanco para nubes
+            shadow_color: Color::new(50, 50, 100), // Color oscuro para sombras
+            glow_color: Color::new(200, 200, 255), // Brillo azul claro
+            noise_scale: 5.0,
+            bands: 0.0,
+            lighting: Lighting::default(),
+        }
+    }
+}
+
+impl GasShader {
+    fn albedo(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let position = fragment.vertex_position;
+
+        // Utiliza la posición del fragmento y el tiempo para generar un "seed" para el ruido.
+        let seed = uniforms.time * 60.0 * position.y * position.x;
+        let mut rng = StdRng::seed_from_u64(seed.abs() as u64);
+        let random_number = rng.gen_range(0..=100);
+
+        let noise_value = uniforms.noise.get_noise_2d(position.x * self.noise_scale, position.z * self.noise_scale);
+        let mut cloud_factor = (noise_value * 0.5 + 0.5).powi(2); // Escala el ruido entre 0 y 1.
+        if self.bands > 0.0 {
+            cloud_factor *= 0.5 + 0.5 * (position.y * self.bands * PI).sin().abs();
+        }
+
+        // Selección de color basado en el número aleatorio para agregar variación.
+        let planet_color = if random_number < 50 {
+            self.base_color * (1.0 - cloud_factor) + self.cloud_color * cloud_factor
+        } else {
+            self.cloud_color * cloud_factor // Predominan las nubes
+        };
+
+        // Sombras sutiles
+        let shadow_factor = (1.0 - noise_value).max(0.0);
+        let shadow_effect = self.shadow_color * shadow_factor * 0.3;
+
+        // Brillo atmosférico basado en la altura
+        let glow_factor = (1.0 - (position.y / 10.0).clamp(0.0, 1.0)).max(0.0);
+        let final_glow = self.glow_color * glow_factor * 0.1;
+
+        planet_color + shadow_effect + final_glow
+    }
+}
+
+impl Shader for GasShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        self.lighting.apply(self.albedo(fragment, uniforms), fragment, uniforms)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct RockyShader {
+    pub base_color: Color,
+    pub accent_color: Color,
+    pub cloud_color: Color,
+    pub noise_scale: f32,
+    // Brillo constante que se suma a todo el planeta
+    pub brightness: f32,
+    #[serde(flatten)]
+    pub lighting: Lighting,
+}
+
+impl Default for RockyShader {
+    fn default() -> Self {
+        RockyShader {
+            base_color: Color::new(50, 50, 200), // Azul profundo.
+            accent_color: Color::new(180, 80, 200), // Púrpura vibrante para los detalles.
+            cloud_color: Color::new(255, 255, 255), // Blanco para las nubes.
+            noise_scale: 2.0,
+            brightness: 0.1,
+            lighting: Lighting::default(),
+        }
+    }
+}
+
+impl RockyShader {
+    fn albedo(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let position = fragment.vertex_position;
+
+        let noise_value = uniforms.noise.get_noise_2d(position.x * self.noise_scale, position.z * self.noise_scale);
+        let cloud_factor = (noise_value * 0.5 + 0.5).powi(3); // Escala el ruido para suavizar la textura.
+
+        // Desplazamiento basado en la posición Y del fragmento para crear capas.
+        let layer_offset = (position.y * 0.5).sin() * 0.5;
+
+        // Nubes en la parte superior del planeta, color de acento en la inferior
+        let planet_color = if position.y > 0.0 {
+            self.base_color * (1.0 - cloud_factor) + self.cloud_color * cloud_factor
+        } else {
+            self.accent_color * (1.0 + layer_offset)
+        };
+
+        let light = (self.brightness * 255.0) as u8;
+        planet_color + Color::new(light, light, light)
+    }
+}
+
+impl Shader for RockyShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        self.lighting.apply(self.albedo(fragment, uniforms), fragment, uniforms)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct ShipShader {
+    pub base_color: Color,
+    pub glow_color: Color,
+    #[serde(flatten)]
+    pub lighting: Lighting,
+}
+
+impl Default for ShipShader {
+    fn default() -> Self {
+        ShipShader {
+            base_color: Color::new(180, 180, 180), // Gris metálico
+            glow_color: Color::new(255, 140, 0), // Brillo anaranjado
+            lighting: Lighting::default(),
+        }
+    }
+}
+
+impl ShipShader {
+    fn albedo(&self, fragment: &Fragment, _uniforms: &Uniforms) -> Color {
+        // Brillo basado en la altura
+        let glow_factor = (1.0 - (fragment.vertex_position.y / 10.0).clamp(0.0, 1.0)).max(0.0);
+        self.base_color + self.glow_color * glow_factor * 0.1
+    }
+}
+
+impl Shader for ShipShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        self.lighting.apply(self.albedo(fragment, uniforms), fragment, uniforms)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct StarShader {
+    pub base_color: Color,
+    pub star_color: Color,
+    pub noise_scale: f32,
+    pub star_brightness: f32,
+    #[serde(flatten)]
+    pub lighting: Lighting,
+}
+
+impl Default for StarShader {
+    fn default() -> Self {
+        StarShader {
+            base_color: Color::new(30, 30, 60), // Azul oscuro para el fondo del planeta
+            star_color: Color::new(255, 255, 255), // Blanco para las estrellas
+            noise_scale: 5.0,
+            star_brightness: 0.7,
+            lighting: Lighting::default(),
+        }
+    }
+}
+
+impl StarShader {
+    fn albedo(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let position = fragment.vertex_position;
+
+        // Utiliza la posición del fragmento y el tiempo para generar un "seed" para el ruido.
+        let seed = uniforms.time * 60.0 * position.y * position.x;
+        let mut rng = StdRng::seed_from_u64(seed.abs() as u64);
+
+        let noise_value = uniforms.noise.get_noise_2d(position.x * self.noise_scale, position.z * self.noise_scale);
+        let star_factor = (noise_value * 0.5 + 0.5).powi(2); // Escala el ruido entre 0 y 1.
+
+        // Determinar si hay una estrella en este fragmento
+        let random_number = rng.gen_range(0..=100);
+        let is_star = random_number < (star_factor * 100.0) as u32;
+
+        if is_star {
+            self.star_color + self.star_color * self.star_brightness
+        } else {
+            self.base_color
+        }
+    }
+}
+
+impl Shader for StarShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        self.lighting.apply(self.albedo(fragment, uniforms), fragment, uniforms)
+    }
+}
+
+// Capa transparente alrededor de un planeta (usar con `blend: Alpha` y una esfera
+// algo más grande que el planeta): casi invisible de frente y más opaca en el
+// borde, donde la mirada atraviesa más atmósfera
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct AtmosphereShader {
+    pub color: Color,
+    // Opacidad en el centro del disco y en el borde
+    pub opacity: f32,
+    pub rim_opacity: f32,
+    // Cuanto mayor, más fino el borde opaco
+    pub rim_power: f32,
+}
+
+impl Default for AtmosphereShader {
+    fn default() -> Self {
+        AtmosphereShader {
+            color: Color::new(120, 170, 255),
+            opacity: 0.05,
+            rim_opacity: 0.8,
+            rim_power: 3.0,
+        }
+    }
+}
+
+impl Shader for AtmosphereShader {
+    fn shade(&self, fragment: &Fragment, uniforms: &Uniforms) -> Color {
+        let view = (uniforms.camera_position - fragment.world_position).normalize();
+        let rim = 1.0 - dot(&fragment.normal, &view).abs().min(1.0);
+        let alpha = self.opacity + (self.rim_opacity - self.opacity) * rim.powf(self.rim_power);
+
+        Lighting::DIFFUSE.apply(self.color, fragment, uniforms).with_alpha(alpha)
+    }
 }
@@ -0,0 +1,161 @@
+use nalgebra_glm::{Mat4, Vec3, Vec4, look_at, perspective};
+use std::f32::consts::PI;
+use crate::{Uniforms, create_viewport_matrix, draw_mesh};
+use crate::color::Color;
+use crate::framebuffer::Framebuffer;
+use crate::light::PointLight;
+use crate::scene::Scene;
+
+pub const SHADOW_MAP_SIZE: usize = 512;
+const SHADOW_NEAR: f32 = 0.05;
+const SHADOW_FAR: f32 = 100.0;
+// Desplazamientos de la posición consultada (hacia la luz y a lo largo de la
+// normal, en unidades del mundo) para evitar el acné de sombras
+const DEPTH_BIAS: f32 = 0.02;
+const NORMAL_OFFSET: f32 = 0.02;
+// Radio del filtro PCF en texeles: (2 * radio + 1)^2 comparaciones
+const PCF_RADIUS: i64 = 1;
+
+// Dirección y vector "arriba" de cada cara del cubo: +X, -X, +Y, -Y, +Z, -Z
+const FACES: [([f32; 3], [f32; 3]); 6] = [
+    ([1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+    ([-1.0, 0.0, 0.0], [0.0, -1.0, 0.0]),
+    ([0.0, 1.0, 0.0], [0.0, 0.0, 1.0]),
+    ([0.0, -1.0, 0.0], [0.0, 0.0, -1.0]),
+    ([0.0, 0.0, 1.0], [0.0, -1.0, 0.0]),
+    ([0.0, 0.0, -1.0], [0.0, -1.0, 0.0]),
+];
+
+struct ShadowFace {
+    // Proyección * vista desde la luz
+    view_projection: Mat4,
+    depth: Vec<f32>,
+}
+
+// Mapa de sombras cúbico de una luz puntual: seis caras de 90° con la profundidad
+// del objeto más cercano a la luz, dibujadas con el mismo rasterizador que la escena.
+// Los búferes se reservan una vez y se reutilizan en cada `render`
+pub struct ShadowMap {
+    light_position: Vec3,
+    size: usize,
+    projection_matrix: Mat4,
+    viewport_matrix: Mat4,
+    faces: Vec<ShadowFace>,
+    // Destino de dibujo; al terminar cada cara su z-buffer se intercambia con el de la cara
+    target: Framebuffer,
+}
+
+impl ShadowMap {
+    pub fn new() -> Self {
+        let size = SHADOW_MAP_SIZE;
+        ShadowMap {
+            light_position: Vec3::zeros(),
+            size,
+            projection_matrix: perspective(1.0, PI / 2.0, SHADOW_NEAR, SHADOW_FAR),
+            viewport_matrix: create_viewport_matrix(size as f32, size as f32),
+            faces: (0..FACES.len())
+                .map(|_| ShadowFace { view_projection: Mat4::identity(), depth: vec![f32::INFINITY; size * size] })
+                .collect(),
+            target: Framebuffer::new(size, size),
+        }
+    }
+
+    // Dibuja la profundidad de todos los cuerpos opacos con malla salvo los que emiten luz
+    pub fn render(&mut self, scene: &Scene, light: &PointLight, time: f32) {
+        self.light_position = light.position;
+
+        for (face, &(direction, up)) in self.faces.iter_mut().zip(FACES.iter()) {
+            let view_matrix = look_at(&light.position, &(light.position + Vec3::from(direction)), &Vec3::from(up));
+            self.target.clear_depth();
+
+            for body in &scene.bodies {
+                let (Some(mesh), Some(material)) = (body.mesh, &body.material) else {
+                    continue;
+                };
+                if body.light.is_some() || !body.raster_state.blend.is_opaque() {
+                    continue;
+                }
+
+                let uniforms = Uniforms {
+                    model_matrix: body.model_matrix(),
+                    view_matrix,
+                    projection_matrix: self.projection_matrix,
+                    viewport_matrix: self.viewport_matrix,
+                    time,
+                    light: *light,
+                    camera_position: light.position,
+                    noise: scene.noises.get(material.noise),
+                    shader: material.shader.as_ref(),
+                    texture: None,
+                    surface: None,
+                    normal_map: None,
+                    shadow: None,
+                };
+
+                for mesh in &scene.models[mesh].meshes {
+                    draw_mesh(&mut self.target, &uniforms, &body.raster_state, mesh, |_| Color::black());
+                }
+            }
+
+            face.view_projection = self.projection_matrix * view_matrix;
+            std::mem::swap(&mut face.depth, &mut self.target.zbuffer);
+        }
+    }
+
+    // Fracción de la luz que llega a `position` (0 en sombra, 1 iluminado),
+    // promediando las comparaciones de los texeles vecinos (PCF)
+    pub fn visibility(&self, position: Vec3, normal: Vec3) -> f32 {
+        let to_light = self.light_position - position;
+        let distance = to_light.norm();
+        if distance == 0.0 {
+            return 1.0;
+        }
+
+        let position = position + to_light / distance * DEPTH_BIAS + normal.normalize() * NORMAL_OFFSET;
+        let face = &self.faces[face_index(position - self.light_position)];
+
+        let clip = face.view_projection * Vec4::new(position.x, position.y, position.z, 1.0);
+        if clip.w <= 0.0 {
+            return 1.0;
+        }
+        let ndc = Vec4::new(clip.x / clip.w, clip.y / clip.w, clip.z / clip.w, 1.0);
+        let screen = self.viewport_matrix * ndc;
+
+        let size = self.size as i64;
+        let (x, y) = (screen.x.floor() as i64, screen.y.floor() as i64);
+        let mut lit = 0;
+        let mut total = 0;
+        for dy in -PCF_RADIUS..=PCF_RADIUS {
+            for dx in -PCF_RADIUS..=PCF_RADIUS {
+                let sx = (x + dx).clamp(0, size - 1) as usize;
+                let sy = (y + dy).clamp(0, size - 1) as usize;
+                if ndc.z <= face.depth[sy * self.size + sx] {
+                    lit += 1;
+                }
+                total += 1;
+            }
+        }
+
+        lit as f32 / total as f32
+    }
+}
+
+impl Default for ShadowMap {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Cara del cubo hacia la que apunta `direction` (el eje de mayor magnitud)
+fn face_index(direction: Vec3) -> usize {
+    let abs = direction.abs();
+    if abs.x >= abs.y && abs.x >= abs.z {
+        if direction.x >= 0.0 { 0 } else { 1 }
+    } else if abs.y >= abs.z {
+        if direction.y >= 0.0 { 2 } else { 3 }
+    } else if direction.z >= 0.0 {
+        4
+    } else {
+        5
+    }
+}
@@ -0,0 +1,121 @@
+use nalgebra_glm::Vec3;
+use std::path::Path;
+use sr_02_line::{Uniforms, create_perspective_matrix, create_view_matrix, create_viewport_matrix, render};
+use sr_02_line::camera::Camera;
+use sr_02_line::color::Color;
+use sr_02_line::light::PointLight;
+use sr_02_line::shadow::ShadowMap;
+use sr_02_line::framebuffer::Framebuffer;
+use sr_02_line::scene::{Body, Scene, SceneError};
+use sr_02_line::bloom::Bloom;
+use sr_02_line::postprocess::Antialiasing;
+use sr_02_line::tonemap::ToneMapping;
+
+pub const WINDOW_WIDTH: usize = 900;
+pub const WINDOW_HEIGHT: usize = 900;
+pub const FRAMEBUFFER_WIDTH: usize = 760;
+pub const FRAMEBUFFER_HEIGHT: usize = 800;
+pub const STAR_COUNT: usize = 400;
+pub const SCENE_PATH: &str = "assets/scenes/solar_system.ron";
+
+// Opciones de imagen que comparten la ventana y el modo sin ventana
+#[derive(Clone, Copy)]
+pub struct RenderOptions {
+    // Muestras por píxel de MSAA (1, 2, 4 u 8)
+    pub samples: usize,
+    pub antialiasing: Antialiasing,
+    pub tone_mapping: ToneMapping,
+    pub bloom: Option<Bloom>,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            samples: 1,
+            antialiasing: Antialiasing::None,
+            tone_mapping: ToneMapping::default(),
+            bloom: None,
+        }
+    }
+}
+
+impl RenderOptions {
+    // Aplica al framebuffer todo salvo el antialiasing, que va en `PostProcess`
+    pub fn configure(&self, framebuffer: &mut Framebuffer) {
+        framebuffer.set_samples(self.samples);
+        framebuffer.set_tone_mapping(self.tone_mapping);
+        framebuffer.set_bloom(self.bloom);
+    }
+}
+
+pub fn load_scene() -> Result<Scene, SceneError> {
+    Scene::load(Path::new(SCENE_PATH))
+}
+
+// `shadow_map` se reutiliza entre cuadros
+pub fn render_frame(framebuffer: &mut Framebuffer, shadow_map: &mut ShadowMap, camera: &Camera, scene: &Scene, time: f32) {
+    framebuffer.clear();
+
+    let view_matrix = create_view_matrix(camera.eye, camera.center, camera.up);
+    let projection_matrix = create_perspective_matrix(WINDOW_WIDTH as f32, WINDOW_HEIGHT as f32);
+    let viewport_matrix = create_viewport_matrix(framebuffer.width as f32, framebuffer.height as f32);
+
+    // Sin un cuerpo que emita luz, se ilumina desde la cámara y no hay sombras
+    let scene_light = scene.light();
+    if let Some(light) = &scene_light {
+        shadow_map.render(scene, light, time);
+    }
+    let shadow = scene_light.is_some().then_some(&*shadow_map);
+    let light = scene_light.unwrap_or_else(|| PointLight::new(camera.eye, Color::new(255, 255, 255), 1.0, 0.05));
+
+    // Primero los cuerpos opacos y después los transparentes, del más lejano al más
+    // cercano a la cámara, para que cada uno se mezcle con lo que tiene detrás
+    let (opaque, mut transparent): (Vec<&Body>, Vec<&Body>) = scene
+        .bodies
+        .iter()
+        .filter(|body| body.mesh.is_some() && body.material.is_some())
+        .partition(|body| body.raster_state.blend.is_opaque());
+    let distance = |body: &Body| (body.world_position() - camera.eye).norm();
+    transparent.sort_by(|a, b| distance(b).total_cmp(&distance(a)));
+
+    for body in opaque.into_iter().chain(transparent) {
+        let (Some(mesh), Some(material)) = (body.mesh, &body.material) else {
+            continue;
+        };
+
+        let model = &scene.models[mesh];
+        for mesh in &model.meshes {
+            let surface = mesh.material.map(|id| &model.surfaces[id]);
+            // La textura del material de la escena tiene prioridad sobre el map_Kd
+            let texture = material.texture.or(surface.and_then(|surface| surface.diffuse_map));
+
+            let uniforms = Uniforms {
+                model_matrix: body.model_matrix(),
+                view_matrix,
+                projection_matrix,
+                viewport_matrix,
+                time,
+                light,
+                camera_position: camera.eye,
+                noise: scene.noises.get(material.noise),
+                shader: material.shader.as_ref(),
+                texture: texture.map(|texture| &scene.textures[texture]),
+                surface,
+                normal_map: surface.and_then(|surface| surface.normal_map).map(|map| &scene.textures[map]),
+                shadow,
+            };
+
+            render(framebuffer, &uniforms, &body.raster_state, mesh);
+        }
+    }
+
+    framebuffer.resolve();
+}
+
+pub fn initial_camera() -> Camera {
+    Camera::new(
+        Vec3::new(0.0, 0.0, 5.0),
+        Vec3::new(0.0, 0.0, 0.0),
+        Vec3::new(0.0, 1.0, 0.0)
+    )
+}
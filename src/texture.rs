@@ -0,0 +1,235 @@
+use nalgebra_glm::{Vec2, Vec3};
+use serde::Deserialize;
+use std::path::Path;
+use crate::color::Color;
+use std::f32::consts::PI;
+
+// Límite de muestras por fragmento con filtrado anisotrópico
+pub const MAX_ANISOTROPY: u32 = 16;
+
+#[derive(Deserialize, Default, PartialEq, Debug, Clone, Copy)]
+pub enum FilterMode {
+    Nearest,
+    #[default]
+    Bilinear,
+    // Bilineal en los dos mipmaps más cercanos y mezcla entre ellos
+    Trilinear,
+}
+
+// Qué hacer con las coordenadas fuera de [0, 1]
+#[derive(Deserialize, Default, PartialEq, Debug, Clone, Copy)]
+pub enum WrapMode {
+    #[default]
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+}
+
+impl WrapMode {
+    // Lleva un índice de texel cualquiera a [0, size)
+    fn apply(self, texel: i64, size: usize) -> usize {
+        let size = size as i64;
+        let wrapped = match self {
+            WrapMode::Repeat => texel.rem_euclid(size),
+            WrapMode::MirroredRepeat => {
+                let period = texel.rem_euclid(2 * size);
+                if period < size { period } else { 2 * size - 1 - period }
+            }
+            WrapMode::ClampToEdge => texel.clamp(0, size - 1),
+        };
+        wrapped as usize
+    }
+}
+
+// Coordenadas equirectangulares (longitud, latitud) de una dirección desde el
+// centro del modelo, para mapas planetarios en mallas sin UVs útiles
+pub fn spherical_uv(direction: Vec3) -> Vec2 {
+    let direction = direction.normalize();
+    Vec2::new(
+        0.5 + direction.z.atan2(direction.x) / (2.0 * PI),
+        0.5 - direction.y.clamp(-1.0, 1.0).asin() / PI,
+    )
+}
+
+struct MipLevel {
+    width: usize,
+    height: usize,
+    pixels: Vec<Color>,
+}
+
+impl MipLevel {
+    // Mitad de tamaño (redondeando hacia arriba) promediando bloques de 2x2
+    fn downsample(&self) -> MipLevel {
+        let width = self.width.div_ceil(2);
+        let height = self.height.div_ceil(2);
+        let mut pixels = Vec::with_capacity(width * height);
+
+        for y in 0..height {
+            for x in 0..width {
+                let x0 = 2 * x;
+                let y0 = 2 * y;
+                let x1 = (x0 + 1).min(self.width - 1);
+                let y1 = (y0 + 1).min(self.height - 1);
+                pixels.push(Color::average(&[
+                    self.pixels[y0 * self.width + x0],
+                    self.pixels[y0 * self.width + x1],
+                    self.pixels[y1 * self.width + x0],
+                    self.pixels[y1 * self.width + x1],
+                ]));
+            }
+        }
+
+        MipLevel { width, height, pixels }
+    }
+}
+
+pub struct Texture {
+    pub width: usize,
+    pub height: usize,
+    // levels[0] es la imagen original; cada nivel mide la mitad que el anterior hasta 1x1
+    levels: Vec<MipLevel>,
+    pub filter: FilterMode,
+    pub wrap_u: WrapMode,
+    pub wrap_v: WrapMode,
+    // Muestras máximas a lo largo del eje largo de la huella del píxel; 1 lo desactiva
+    pub max_anisotropy: u32,
+}
+
+impl Texture {
+    pub fn new(width: usize, height: usize, pixels: Vec<Color>) -> Self {
+        assert_eq!(pixels.len(), width * height, "la textura necesita width * height pixeles");
+
+        let mut levels = vec![MipLevel { width, height, pixels }];
+        while let Some(last) = levels.last().filter(|level| level.width > 1 || level.height > 1) {
+            let next = last.downsample();
+            levels.push(next);
+        }
+
+        Texture {
+            width,
+            height,
+            levels,
+            filter: FilterMode::default(),
+            wrap_u: WrapMode::default(),
+            wrap_v: WrapMode::default(),
+            max_anisotropy: 1,
+        }
+    }
+
+    // PNG o JPEG con colores en sRGB; el canal alfa pasa al alfa del color (opaco
+    // si la imagen no tiene). Los mipmaps se generan aquí.
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, image::ImageError> {
+        Texture::load_with(path, Color::new)
+    }
+
+    // Como `load`, para imágenes que guardan datos y no colores (mapas de normales):
+    // los valores se usan tal cual, sin decodificar sRGB
+    pub fn load_linear<P: AsRef<Path>>(path: P) -> Result<Self, image::ImageError> {
+        Texture::load_with(path, |r, g, b| Color::from_linear(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0))
+    }
+
+    fn load_with<P: AsRef<Path>>(path: P, decode: impl Fn(u8, u8, u8) -> Color) -> Result<Self, image::ImageError> {
+        let image = image::open(path)?.to_rgba8();
+        let (width, height) = image.dimensions();
+        let pixels = image
+            .pixels()
+            .map(|p| decode(p[0], p[1], p[2]).with_alpha(p[3] as f32 / 255.0))
+            .collect();
+        Ok(Texture::new(width as usize, height as usize, pixels))
+    }
+
+    pub fn mip_levels(&self) -> usize {
+        self.levels.len()
+    }
+
+    pub fn texel(&self, x: i64, y: i64) -> Color {
+        self.level_texel(0, x, y)
+    }
+
+    fn level_texel(&self, level: usize, x: i64, y: i64) -> Color {
+        let level = &self.levels[level];
+        let x = self.wrap_u.apply(x, level.width);
+        let y = self.wrap_v.apply(y, level.height);
+        level.pixels[y * level.width + x]
+    }
+
+    // `uv` con el origen arriba a la izquierda (Obj ya invierte la v de los archivos).
+    // Sin derivadas no hay mipmaps: siempre lee el nivel 0.
+    pub fn sample(&self, uv: Vec2) -> Color {
+        match self.filter {
+            FilterMode::Nearest => self.sample_nearest(0, uv),
+            FilterMode::Bilinear | FilterMode::Trilinear => self.sample_bilinear(0, uv),
+        }
+    }
+
+    // Muestrea con el nivel de detalle que indican las derivadas de `uv` en pantalla
+    // (cuánto cambia al avanzar un píxel en x y en y)
+    pub fn sample_grad(&self, uv: Vec2, uv_dx: Vec2, uv_dy: Vec2) -> Color {
+        let size = Vec2::new(self.width as f32, self.height as f32);
+        let dx = uv_dx.component_mul(&size);
+        let dy = uv_dy.component_mul(&size);
+        let (length_x, length_y) = (dx.norm(), dy.norm());
+
+        let (major, minor, axis) = if length_x >= length_y { (length_x, length_y, uv_dx) } else { (length_y, length_x, uv_dy) };
+
+        // Con anisotropía se toman varias muestras a lo largo del eje largo y el
+        // nivel se elige con el eje corto
+        let samples = if self.max_anisotropy > 1 && minor > 0.0 {
+            ((major / minor).ceil() as u32).clamp(1, self.max_anisotropy.min(MAX_ANISOTROPY))
+        } else {
+            1
+        };
+        let lod = (major / samples as f32).max(1e-8).log2().max(0.0);
+
+        if samples == 1 {
+            return self.sample_lod(uv, lod);
+        }
+
+        let colors: Vec<Color> = (0..samples)
+            .map(|i| {
+                let offset = (i as f32 + 0.5) / samples as f32 - 0.5;
+                self.sample_lod(uv + axis * offset, lod)
+            })
+            .collect();
+        Color::average(&colors)
+    }
+
+    fn sample_lod(&self, uv: Vec2, lod: f32) -> Color {
+        let max_level = self.levels.len() - 1;
+
+        match self.filter {
+            FilterMode::Nearest => self.sample_nearest((lod.round() as usize).min(max_level), uv),
+            FilterMode::Bilinear => self.sample_bilinear((lod.round() as usize).min(max_level), uv),
+            FilterMode::Trilinear => {
+                let lod = lod.min(max_level as f32);
+                let lower = lod.floor() as usize;
+                let upper = (lower + 1).min(max_level);
+                let fine = self.sample_bilinear(lower, uv);
+                let coarse = self.sample_bilinear(upper, uv);
+                fine.lerp(&coarse, lod - lower as f32)
+            }
+        }
+    }
+
+    fn sample_nearest(&self, level: usize, uv: Vec2) -> Color {
+        let MipLevel { width, height, .. } = self.levels[level];
+        let x = uv.x * width as f32;
+        let y = uv.y * height as f32;
+        self.level_texel(level, x.floor() as i64, y.floor() as i64)
+    }
+
+    fn sample_bilinear(&self, level: usize, uv: Vec2) -> Color {
+        let MipLevel { width, height, .. } = self.levels[level];
+
+        // Los centros de los texeles están en los medios enteros
+        let x = uv.x * width as f32 - 0.5;
+        let y = uv.y * height as f32 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let (x0, y0) = (x0 as i64, y0 as i64);
+
+        let top = self.level_texel(level, x0, y0).lerp(&self.level_texel(level, x0 + 1, y0), tx);
+        let bottom = self.level_texel(level, x0, y0 + 1).lerp(&self.level_texel(level, x0 + 1, y0 + 1), tx);
+        top.lerp(&bottom, ty)
+    }
+}
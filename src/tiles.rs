@@ -0,0 +1,167 @@
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+use crate::color::Color;
+use crate::fragment::Fragment;
+use crate::framebuffer::{Framebuffer, Tile};
+use crate::pipeline::RasterState;
+use crate::triangle::{row_range, triangle};
+use crate::vertex::Vertex;
+
+// Filas por franja: suficientes para repartir trabajo entre núcleos sin que
+// cada triángulo termine en demasiadas franjas
+pub const TILE_ROWS: usize = 32;
+// Las franjas empiezan en filas pares, así ningún cuadro de 2x2 queda partido entre
+// dos franjas. triangle() alinea los cuadros a filas pares de la pantalla, de modo que
+// las derivadas coinciden con las del framebuffer entero
+const _: () = assert!(TILE_ROWS.is_multiple_of(2));
+
+// Rasteriza los triángulos por franjas. Cada franja los procesa en el mismo orden
+// que el camino de un solo hilo, así que el resultado es idéntico bit a bit.
+// `triangles` son índices sobre `vertices`, ya en coordenadas de pantalla.
+pub fn rasterize<F>(
+    framebuffer: &mut Framebuffer,
+    raster_state: &RasterState,
+    vertices: &[Vertex],
+    triangles: &[[usize; 3]],
+    shade: F,
+)
+where
+    F: Fn(&Fragment) -> Color + Sync,
+{
+    let mut tiles = framebuffer.tiles_mut(TILE_ROWS);
+    let bins = bin_triangles(vertices, triangles, tiles.len());
+
+    #[cfg(feature = "parallel")]
+    tiles
+        .par_iter_mut()
+        .zip(bins.par_iter())
+        .for_each(|(tile, bin)| rasterize_tile(tile, raster_state, vertices, triangles, bin, &shade));
+
+    #[cfg(not(feature = "parallel"))]
+    tiles
+        .iter_mut()
+        .zip(bins.iter())
+        .for_each(|(tile, bin)| rasterize_tile(tile, raster_state, vertices, triangles, bin, &shade));
+}
+
+fn rasterize_tile<F>(
+    tile: &mut Tile,
+    raster_state: &RasterState,
+    vertices: &[Vertex],
+    triangles: &[[usize; 3]],
+    bin: &[usize],
+    shade: &F,
+) where
+    F: Fn(&Fragment) -> Color,
+{
+    for &index in bin {
+        let [a, b, c] = triangles[index];
+        triangle(&vertices[a], &vertices[b], &vertices[c], tile, raster_state, shade);
+    }
+}
+
+// Índices de los triángulos que tocan cada franja, en orden de dibujo
+fn bin_triangles(vertices: &[Vertex], triangles: &[[usize; 3]], tile_count: usize) -> Vec<Vec<usize>> {
+    let mut bins = vec![Vec::new(); tile_count];
+    let last_tile = tile_count as i64 - 1;
+
+    for (index, &[a, b, c]) in triangles.iter().enumerate() {
+        let (min_y, max_y) = row_range(&vertices[a], &vertices[b], &vertices[c]);
+        let first = (min_y.max(0) / TILE_ROWS as i64).min(last_tile);
+        let last = (max_y / TILE_ROWS as i64).min(last_tile);
+
+        for bin in bins.iter_mut().take((last + 1).max(0) as usize).skip(first as usize) {
+            bin.push(index);
+        }
+    }
+
+    bins
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pipeline::BlendMode;
+    use nalgebra_glm::{Vec2, Vec3};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    const WIDTH: usize = 97;
+    const HEIGHT: usize = 110;
+
+    // Dos capas de cuadrados con vértices desplazados al azar, en coordenadas de
+    // pantalla, que cruzan varias franjas (la última incompleta)
+    fn mesh() -> (Vec<Vertex>, Vec<[usize; 3]>) {
+        let mut rng = StdRng::seed_from_u64(7);
+        let (columns, rows) = (9, 11);
+        let mut vertices = Vec::new();
+        let mut triangles = Vec::new();
+
+        for layer in 0..2 {
+            let first = vertices.len();
+            for j in 0..=rows {
+                for i in 0..=columns {
+                    let x = i as f32 * WIDTH as f32 / columns as f32 + rng.gen_range(-3.0..3.0);
+                    let y = j as f32 * HEIGHT as f32 / rows as f32 + rng.gen_range(-3.0..3.0);
+                    let position = Vec3::new(i as f32, j as f32, layer as f32);
+                    let mut vertex = Vertex::new(position, Vec3::new(0.0, 0.0, 1.0), Vec2::new(x / 7.0, y / 5.0), 0.0);
+                    vertex.transformed_position = Vec3::new(x, y, rng.gen_range(0.0..1.0));
+                    vertex.clip_position.w = rng.gen_range(0.5..2.0);
+                    vertices.push(vertex);
+                }
+            }
+
+            for j in 0..rows {
+                for i in 0..columns {
+                    let corner = first + j * (columns + 1) + i;
+                    let below = corner + columns + 1;
+                    triangles.push([corner, corner + 1, below]);
+                    triangles.push([corner + 1, below + 1, below]);
+                }
+            }
+        }
+
+        (vertices, triangles)
+    }
+
+    // El color depende de los atributos y de sus derivadas por cuadro de 2x2
+    fn shade(fragment: &Fragment) -> Color {
+        Color::from_linear(
+            fragment.tex_coords.x.fract().abs(),
+            fragment.vertex_position.y * 0.1 + fragment.tex_coords_dx.x,
+            fragment.depth + fragment.tex_coords_dy.y,
+        )
+        .with_alpha(0.5)
+    }
+
+    fn assert_matches_single_tile(samples: usize, raster_state: RasterState) {
+        let (vertices, triangles) = mesh();
+
+        let mut tiled = Framebuffer::new(WIDTH, HEIGHT);
+        tiled.set_samples(samples);
+        rasterize(&mut tiled, &raster_state, &vertices, &triangles, shade);
+
+        let mut single = Framebuffer::new(WIDTH, HEIGHT);
+        single.set_samples(samples);
+        let mut tile = single.as_tile();
+        for &[a, b, c] in &triangles {
+            triangle(&vertices[a], &vertices[b], &vertices[c], &mut tile, &raster_state, shade);
+        }
+
+        assert!(tiled.hdr == single.hdr, "las franjas cambian la imagen con {} muestras", samples);
+        assert!(tiled.hdr.iter().any(|&color| color != Color::black()));
+    }
+
+    #[test]
+    fn tiles_match_single_tile() {
+        for samples in [1, 4] {
+            assert_matches_single_tile(samples, RasterState::default());
+        }
+    }
+
+    #[test]
+    fn tiles_match_single_tile_with_blending() {
+        let raster_state = RasterState { blend: BlendMode::Alpha, depth_write: false, ..RasterState::default() };
+        assert_matches_single_tile(8, raster_state);
+    }
+}
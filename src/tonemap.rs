@@ -0,0 +1,59 @@
+use std::fmt;
+use crate::color::Color;
+
+// Operador que lleva el color HDR (lineal, sin límite) a [0, 1] antes de codificarlo en sRGB
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToneMapper {
+    // Recorta en 1.0, como el búfer de 8 bits de antes
+    Clamp,
+    // c / (1 + c): nunca satura, aplana los brillos
+    Reinhard,
+    // Aproximación de Narkowicz a la curva ACES de cine, con más contraste
+    Aces,
+    // 1 - e^(-c), una curva de exposición fotográfica
+    Exposure,
+}
+
+pub const TONE_MAPPERS: [ToneMapper; 4] = [ToneMapper::Clamp, ToneMapper::Reinhard, ToneMapper::Aces, ToneMapper::Exposure];
+
+impl fmt::Display for ToneMapper {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ToneMapper::Clamp => "clamp",
+            ToneMapper::Reinhard => "Reinhard",
+            ToneMapper::Aces => "ACES",
+            ToneMapper::Exposure => "exposición",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct ToneMapping {
+    pub operator: ToneMapper,
+    // Multiplica el color antes del operador
+    pub exposure: f32,
+}
+
+impl Default for ToneMapping {
+    fn default() -> Self {
+        ToneMapping { operator: ToneMapper::Aces, exposure: 1.0 }
+    }
+}
+
+impl ToneMapping {
+    // Color HDR -> píxel sRGB de 8 bits
+    pub fn map(&self, color: Color) -> u32 {
+        let curve = |c: f32| {
+            let c = (c * self.exposure).max(0.0);
+            match self.operator {
+                ToneMapper::Clamp => c,
+                ToneMapper::Reinhard => c / (1.0 + c),
+                ToneMapper::Aces => (c * (2.51 * c + 0.03)) / (c * (2.43 * c + 0.59) + 0.14),
+                ToneMapper::Exposure => 1.0 - (-c).exp(),
+            }
+        };
+
+        Color::from_unit(curve(color.r()), curve(color.g()), curve(color.b())).to_hex()
+    }
+}
@@ -1,77 +1,331 @@
-use nalgebra_glm::{Vec3, dot};
+use nalgebra_glm::{Vec2, Vec3};
 use crate::fragment::Fragment;
+use crate::framebuffer::{Tile, sample_offsets};
+use crate::pipeline::RasterState;
 use crate::vertex::Vertex;
 use crate::color::Color;
 
-pub fn triangle(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> Vec<Fragment> {
-  let mut fragments = Vec::new();
+// Las aristas se evalúan en punto fijo con 8 bits de subpíxel, así dos triángulos
+// que comparten una arista obtienen exactamente los mismos valores
+const SUBPIXEL_BITS: u32 = 8;
+const SUBPIXEL_ONE: i64 = 1 << SUBPIXEL_BITS;
+const SUBPIXEL_HALF: i64 = SUBPIXEL_ONE / 2;
+// Las posiciones de las muestras de MSAA vienen en dieciseisavos de píxel
+const SAMPLE_OFFSET_SCALE: i64 = SUBPIXEL_ONE / 16;
+const MAX_SAMPLES: usize = 8;
+
+#[derive(Clone, Copy)]
+struct FixedPoint {
+  x: i64,
+  y: i64,
+}
+
+impl FixedPoint {
+  fn from_screen(v: &Vec3) -> Self {
+    FixedPoint {
+      x: (v.x * SUBPIXEL_ONE as f32).round() as i64,
+      y: (v.y * SUBPIXEL_ONE as f32).round() as i64,
+    }
+  }
+}
+
+// Arista a -> b con la orientación ya corregida para que el interior sea positivo
+struct Edge {
+  a: FixedPoint,
+  b: FixedPoint,
+  sign: i64,
+  // Regla top-left: los píxeles exactamente sobre la arista solo cuentan si es
+  // una arista superior o izquierda
+  bias: i64,
+}
+
+impl Edge {
+  fn new(a: FixedPoint, b: FixedPoint, sign: i64) -> Self {
+    // Gradiente de la función de arista = dirección hacia el interior (y crece hacia abajo)
+    let normal_x = sign * (b.y - a.y);
+    let normal_y = -sign * (b.x - a.x);
+    let is_top_left = normal_x > 0 || (normal_x == 0 && normal_y > 0);
+
+    Edge { a, b, sign, bias: if is_top_left { 0 } else { -1 } }
+  }
+
+  fn evaluate(&self, p: FixedPoint) -> i64 {
+    self.sign * fixed_edge_function(self.a, self.b, p)
+  }
+
+  fn covers(&self, value: i64) -> bool {
+    value + self.bias >= 0
+  }
+}
+
+// Rasteriza la parte del triángulo que cae en `tile`. `shade` se llama una vez por
+// píxel (en su centro) si alguna de sus muestras cubiertas pasa la prueba de
+// profundidad; el color se mezcla (según `raster_state`) solo en esas muestras.
+pub fn triangle<F>(v1: &Vertex, v2: &Vertex, v3: &Vertex, tile: &mut Tile, raster_state: &RasterState, mut shade: F)
+where
+  F: FnMut(&Fragment) -> Color,
+{
   let (a, b, c) = (v1.transformed_position, v2.transformed_position, v3.transformed_position);
+  let (fa, fb, fc) = (FixedPoint::from_screen(&a), FixedPoint::from_screen(&b), FixedPoint::from_screen(&c));
+
+  let area = fixed_edge_function(fa, fb, fc);
+  if area == 0 {
+    return;
+  }
 
-  let (min_x, min_y, max_x, max_y) = calculate_bounding_box(&a, &b, &c);
+  // Funciona con ambos órdenes de vértices: se normaliza para que el área sea positiva
+  let sign = area.signum();
+  let triangle_area = (area * sign) as f32;
+  let edges = [Edge::new(fb, fc, sign), Edge::new(fc, fa, sign), Edge::new(fa, fb, sign)];
 
-  let light_dir = Vec3::new(0.0, 0.0, 1.0);
+  let offsets = sample_offsets(tile.samples);
+  let margin = offsets.iter().map(|&(x, y)| x.abs().max(y.abs())).max().unwrap_or(0) * SAMPLE_OFFSET_SCALE;
+  let (min_x, min_y, max_x, max_y) = calculate_bounding_box(fa, fb, fc, margin);
+  let min_x = min_x.max(0);
+  let min_y = min_y.max(tile.y_start as i64);
+  let max_x = max_x.min(tile.width as i64 - 1);
+  let max_y = max_y.min(tile.y_end() as i64 - 1);
 
-  let triangle_area = edge_function(&a, &b, &c);
+  // Se recorre en cuadros de 2x2 alineados a coordenadas pares, como una GPU: los
+  // atributos de los cuatro píxeles se calculan siempre (aunque no estén cubiertos)
+  // para obtener las derivadas en pantalla que usan los mipmaps
+  for quad_y in (min_y & !1..=max_y).step_by(2) {
+    for quad_x in (min_x & !1..=max_x).step_by(2) {
+      let mut quad = [[QuadPixel::default(); 2]; 2];
 
-  for y in min_y..=max_y {
-    for x in min_x..=max_x {
-      let point = Vec3::new(x as f32 + 0.5, y as f32 + 0.5, 0.0);
+      for (dy, row) in quad.iter_mut().enumerate() {
+        for (dx, pixel) in row.iter_mut().enumerate() {
+          let point = FixedPoint {
+            x: (quad_x + dx as i64) * SUBPIXEL_ONE + SUBPIXEL_HALF,
+            y: (quad_y + dy as i64) * SUBPIXEL_ONE + SUBPIXEL_HALF,
+          };
 
-      let (w1, w2, w3) = barycentric_coordinates(&point, &a, &b, &c, triangle_area);
+          // Cobertura y profundidad por muestra; con una sola muestra es el centro
+          for (sample, &(offset_x, offset_y)) in offsets.iter().enumerate() {
+            let point = FixedPoint {
+              x: point.x + offset_x * SAMPLE_OFFSET_SCALE,
+              y: point.y + offset_y * SAMPLE_OFFSET_SCALE,
+            };
+            let e = [edges[0].evaluate(point), edges[1].evaluate(point), edges[2].evaluate(point)];
+            if edges[0].covers(e[0]) && edges[1].covers(e[1]) && edges[2].covers(e[2]) {
+              pixel.coverage |= 1 << sample;
+              let (w1, w2, w3) = (e[0] as f32 / triangle_area, e[1] as f32 / triangle_area, e[2] as f32 / triangle_area);
+              pixel.sample_depths[sample] = a.z * w1 + b.z * w2 + c.z * w3;
+            }
+          }
 
-      if w1 >= 0.0 && w1 <= 1.0 && 
-         w2 >= 0.0 && w2 <= 1.0 &&
-         w3 >= 0.0 && w3 <= 1.0 {
+          let e = [edges[0].evaluate(point), edges[1].evaluate(point), edges[2].evaluate(point)];
+          pixel.weights = (e[0] as f32 / triangle_area, e[1] as f32 / triangle_area, e[2] as f32 / triangle_area);
 
-        let normal = v1.transformed_normal * w1 + v2.transformed_normal * w2 + v3.transformed_normal * w3;
-        let normal = normal.normalize();
+          // La profundidad en pantalla es afín, pero los demás atributos se corrigen por perspectiva
+          let (w1, w2, w3) = pixel.weights;
+          let (p1, p2, p3) = perspective_weights(w1, w2, w3, v1.clip_position.w, v2.clip_position.w, v3.clip_position.w);
+          pixel.perspective = (p1, p2, p3);
+          pixel.vertex_position = v1.position * p1 + v2.position * p2 + v3.position * p3;
+          pixel.world_position = v1.world_position * p1 + v2.world_position * p2 + v3.world_position * p3;
+          pixel.tex_coords = v1.tex_coords * p1 + v2.tex_coords * p2 + v3.tex_coords * p3;
+        }
+      }
+
+      // Derivadas gruesas: las mismas para los cuatro píxeles del cuadro
+      let tex_coords_dx = finite_or_zero(quad[0][1].tex_coords - quad[0][0].tex_coords);
+      let tex_coords_dy = finite_or_zero(quad[1][0].tex_coords - quad[0][0].tex_coords);
+      let vertex_position_dx = finite_or_zero(quad[0][1].vertex_position - quad[0][0].vertex_position);
+      let vertex_position_dy = finite_or_zero(quad[1][0].vertex_position - quad[0][0].vertex_position);
+
+      for (dy, row) in quad.iter().enumerate() {
+        for (dx, pixel) in row.iter().enumerate() {
+          let (x, y) = (quad_x + dx as i64, quad_y + dy as i64);
+          if pixel.coverage == 0 || x < min_x || x > max_x || y < min_y || y > max_y {
+            continue;
+          }
 
-        let intensity = dot(&normal, &light_dir).max(0.0);
+          // Prueba de profundidad temprana: los fragmentos ocultos no llegan al shader
+          let visible = (0..offsets.len())
+            .filter(|&sample| pixel.coverage & (1 << sample) != 0)
+            .filter(|&sample| tile.depth_test(x as usize, y as usize, sample, pixel.sample_depths[sample]))
+            .fold(0u8, |mask, sample| mask | (1 << sample));
+          if visible == 0 {
+            continue;
+          }
 
-        let base_color = Color::new(100, 100, 100);
-        let lit_color = base_color * intensity;
+          let (w1, w2, w3) = pixel.weights;
+          let depth = a.z * w1 + b.z * w2 + c.z * w3;
 
-        let depth = a.z * w1 + b.z * w2 + c.z * w3;
+          let (p1, p2, p3) = pixel.perspective;
+          let normal = v1.transformed_normal * p1 + v2.transformed_normal * p2 + v3.transformed_normal * p3;
+          let normal = normal.normalize();
 
-        let vertex_position = v1.position * w1 + v2.position * w2 + v3.position * w3;
+          let color = v1.color * p1 + v2.color * p2 + v3.color * p3;
 
-        fragments.push(
-            Fragment::new(
-                x as f32,
-                y as f32,
-                lit_color,
-                depth,
-                normal,
-                intensity,
-                vertex_position,
-            )
-        );
+          let fragment = Fragment {
+            position: Vec2::new(x as f32, y as f32),
+            color,
+            depth,
+            normal,
+            world_position: pixel.world_position,
+            vertex_position: pixel.vertex_position,
+            vertex_position_dx,
+            vertex_position_dy,
+            tex_coords: pixel.tex_coords,
+            tex_coords_dx,
+            tex_coords_dy,
+          };
+
+          let color = shade(&fragment);
+          for sample in (0..offsets.len()).filter(|&sample| visible & (1 << sample) != 0) {
+            tile.point(x as usize, y as usize, sample, color, pixel.sample_depths[sample], raster_state);
+          }
+        }
       }
     }
   }
+}
 
-  fragments
+// Un píxel de un cuadro de 2x2
+#[derive(Clone, Copy)]
+struct QuadPixel {
+  // Un bit por muestra cubierta
+  coverage: u8,
+  sample_depths: [f32; MAX_SAMPLES],
+  weights: (f32, f32, f32),
+  perspective: (f32, f32, f32),
+  vertex_position: Vec3,
+  world_position: Vec3,
+  tex_coords: Vec2,
 }
 
-fn calculate_bounding_box(v1: &Vec3, v2: &Vec3, v3: &Vec3) -> (i32, i32, i32, i32) {
-    let min_x = v1.x.min(v2.x).min(v3.x).floor() as i32;
-    let min_y = v1.y.min(v2.y).min(v3.y).floor() as i32;
-    let max_x = v1.x.max(v2.x).max(v3.x).ceil() as i32;
-    let max_y = v1.y.max(v2.y).max(v3.y).ceil() as i32;
+impl Default for QuadPixel {
+  fn default() -> Self {
+    QuadPixel {
+      coverage: 0,
+      sample_depths: [0.0; MAX_SAMPLES],
+      weights: (0.0, 0.0, 0.0),
+      perspective: (0.0, 0.0, 0.0),
+      vertex_position: Vec3::zeros(),
+      world_position: Vec3::zeros(),
+      tex_coords: Vec2::zeros(),
+    }
+  }
+}
+
+// Los píxeles auxiliares lejos del triángulo pueden extrapolar a infinito
+fn finite_or_zero<const N: usize>(v: nalgebra_glm::TVec<f32, N>) -> nalgebra_glm::TVec<f32, N> {
+  if v.iter().all(|c| c.is_finite()) { v } else { nalgebra_glm::TVec::<f32, N>::zeros() }
+}
+
+// Área con signo (x2) en pantalla; la misma que usa el rasterizador
+pub fn signed_area(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> f32 {
+    edge_function(&v1.transformed_position, &v2.transformed_position, &v3.transformed_position)
+}
+
+// Primera y última fila que puede tocar el triángulo, para repartirlo entre franjas.
+// Incluye cualquier posición de muestra dentro del píxel
+pub fn row_range(v1: &Vertex, v2: &Vertex, v3: &Vertex) -> (i64, i64) {
+    let (_, min_y, _, max_y) = calculate_bounding_box(
+        FixedPoint::from_screen(&v1.transformed_position),
+        FixedPoint::from_screen(&v2.transformed_position),
+        FixedPoint::from_screen(&v3.transformed_position),
+        SUBPIXEL_HALF,
+    );
+
+    (min_y, max_y)
+}
+
+// Píxeles con alguna muestra que puede caer dentro del triángulo; `margin` es la
+// mayor distancia (en subpíxeles) de una muestra al centro del píxel
+fn calculate_bounding_box(v1: FixedPoint, v2: FixedPoint, v3: FixedPoint, margin: i64) -> (i64, i64, i64, i64) {
+    let min_x = (v1.x.min(v2.x).min(v3.x) - SUBPIXEL_HALF - margin).div_euclid(SUBPIXEL_ONE);
+    let min_y = (v1.y.min(v2.y).min(v3.y) - SUBPIXEL_HALF - margin).div_euclid(SUBPIXEL_ONE);
+    let max_x = (v1.x.max(v2.x).max(v3.x) - SUBPIXEL_HALF + margin).div_euclid(SUBPIXEL_ONE);
+    let max_y = (v1.y.max(v2.y).max(v3.y) - SUBPIXEL_HALF + margin).div_euclid(SUBPIXEL_ONE);
 
     (min_x, min_y, max_x, max_y)
 }
 
-fn barycentric_coordinates(p: &Vec3, a: &Vec3, b: &Vec3, c: &Vec3, area: f32) -> (f32, f32, f32) {
-    let w1 = edge_function(b, c, p) / area;
-    let w2 = edge_function(c, a, p) / area;
-    let w3 = edge_function(a, b, p) / area;
+// Convierte coordenadas baricéntricas de pantalla en coordenadas del espacio del objeto
+fn perspective_weights(w1: f32, w2: f32, w3: f32, clip_w1: f32, clip_w2: f32, clip_w3: f32) -> (f32, f32, f32) {
+    let p1 = w1 / clip_w1;
+    let p2 = w2 / clip_w2;
+    let p3 = w3 / clip_w3;
+    let sum = p1 + p2 + p3;
 
-    (w1, w2, w3)
+    (p1 / sum, p2 / sum, p3 / sum)
 }
 
 fn edge_function(a: &Vec3, b: &Vec3, c: &Vec3) -> f32 {
     (c.x - a.x) * (b.y - a.y) - (c.y - a.y) * (b.x - a.x)
 }
 
+fn fixed_edge_function(a: FixedPoint, b: FixedPoint, c: FixedPoint) -> i64 {
+    (c.x - a.x) * (b.y - a.y) - (c.y - a.y) * (b.x - a.x)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::framebuffer::Framebuffer;
+    use crate::pipeline::{BlendMode, RasterState};
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use std::f32::consts::PI;
+
+    const SIZE: usize = 48;
+
+    fn screen_vertex(x: f32, y: f32) -> Vertex {
+        Vertex { transformed_position: Vec3::new(x, y, 0.5), ..Vertex::default() }
+    }
+
+    // Abanico alrededor de un centro al azar cuyo borde rodea todo el framebuffer.
+    // Con `snap` los vértices caen en múltiplos de medio píxel, así muchas aristas
+    // pasan justo por los centros (y las muestras) de los píxeles
+    fn fan(rng: &mut StdRng, snap: bool) -> Vec<[Vertex; 3]> {
+        let round = |value: f32| if snap { (value * 2.0).round() / 2.0 } else { value };
+        let size = SIZE as f32;
+        let center = (round(rng.gen_range(8.0..size - 8.0)), round(rng.gen_range(8.0..size - 8.0)));
+
+        let spokes = rng.gen_range(5..24);
+        let start = rng.gen_range(0.0..2.0 * PI);
+        let rim: Vec<(f32, f32)> = (0..spokes)
+            .map(|i| {
+                let angle = start + 2.0 * PI * i as f32 / spokes as f32;
+                (round(center.0 + angle.cos() * size * 2.0), round(center.1 + angle.sin() * size * 2.0))
+            })
+            .collect();
 
+        (0..spokes)
+            .map(|i| {
+                let (a, b) = (rim[i], rim[(i + 1) % spokes]);
+                // Alterna el orden de los vértices: la regla no depende del sentido
+                if i % 2 == 0 {
+                    [screen_vertex(center.0, center.1), screen_vertex(a.0, a.1), screen_vertex(b.0, b.1)]
+                } else {
+                    [screen_vertex(b.0, b.1), screen_vertex(a.0, a.1), screen_vertex(center.0, center.1)]
+                }
+            })
+            .collect()
+    }
+
+    // Cada muestra del framebuffer se escribe exactamente una vez: sin huecos ni
+    // píxeles repetidos sobre las aristas compartidas
+    #[test]
+    fn shared_edges_cover_each_sample_once() {
+        let raster_state = RasterState { blend: BlendMode::Additive, depth_write: false, ..RasterState::default() };
+        let mut rng = StdRng::seed_from_u64(10);
+
+        for samples in [1, 4, 8] {
+            for round in 0..20 {
+                let mut framebuffer = Framebuffer::new(SIZE, SIZE);
+                framebuffer.set_samples(samples);
+                let mut tile = framebuffer.as_tile();
+                for [v1, v2, v3] in fan(&mut rng, round % 2 == 0) {
+                    triangle(&v1, &v2, &v3, &mut tile, &raster_state, |_| Color::from_linear(1.0, 0.0, 0.0));
+                }
+
+                for (index, color) in framebuffer.hdr.iter().enumerate() {
+                    assert_eq!(color.r(), 1.0, "muestra {} escrita {} veces con {} muestras", index, color.r(), samples);
+                }
+            }
+        }
+    }
+}
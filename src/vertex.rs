@@ -1,4 +1,4 @@
-use nalgebra_glm::{Vec2, Vec3};
+use nalgebra_glm::{Vec2, Vec3, Vec4};
 use crate::color::Color;
 
 #[derive(Clone, Debug)]
@@ -9,6 +9,8 @@ pub struct Vertex {
     pub color: Color,
     pub transformed_position: Vec3,
     pub transformed_normal: Vec3,
+    pub world_position: Vec3,
+    pub clip_position: Vec4, // Posición en espacio de recorte, antes de dividir por w
     pub elevation: f32, // Nueva propiedad para la elevación
 }
 
@@ -21,6 +23,8 @@ impl Vertex {
             color: Color::black(),
             transformed_position: position,
             transformed_normal: normal,
+            world_position: position,
+            clip_position: Vec4::new(position.x, position.y, position.z, 1.0),
             elevation, // Inicializar la elevación
         }
     }
@@ -33,6 +37,8 @@ impl Vertex {
             color,
             transformed_position: Vec3::new(0.0, 0.0, 0.0),
             transformed_normal: Vec3::new(0.0, 0.0, 0.0),
+            world_position: position,
+            clip_position: Vec4::new(position.x, position.y, position.z, 1.0),
             elevation: 0.0, // Inicializar la elevación a 0
         }
     }
@@ -63,6 +69,8 @@ impl Default for Vertex {
             color: Color::black(),
             transformed_position: Vec3::new(0.0, 0.0, 0.0),
             transformed_normal: Vec3::new(0.0, 1.0, 0.0),
+            world_position: Vec3::new(0.0, 0.0, 0.0),
+            clip_position: Vec4::new(0.0, 0.0, 0.0, 1.0),
             elevation: 0.0, // Inicializar la elevación a 0
         }
     }